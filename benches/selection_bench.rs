@@ -9,7 +9,7 @@ goals:
   - "Support global users"
 constraints:
   monthly_cost_usd_max: 1000
-  region_allow: ["us-east", "eu-west", "asia-pacific"]
+  region_allow: ["us-east", "eu-west", "apac"]
 traffic_profile:
   rps_peak: 50000
   global: true
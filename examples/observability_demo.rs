@@ -48,14 +48,9 @@ prefs:
     println!("\n=== Selection Complete ===");
     println!("Stack selected:");
     println!("  language: {}", plan.stack.language);
-    println!("  frontend: {}", plan.stack.frontend);
-    println!("  backend: {}", plan.stack.backend);
-    println!("  database: {}", plan.stack.database);
-    println!("  cache: {}", plan.stack.cache);
-    println!("  queue: {}", plan.stack.queue);
-    println!("  ai: {}", plan.stack.ai.join(", "));
-    println!("  infra: {}", plan.stack.infra);
-    println!("  ci_cd: {}", plan.stack.ci_cd);
+    for (category, entry) in &plan.stack.components {
+        println!("  {category}: {entry}");
+    }
     println!("Total monthly cost: ${}", plan.estimated.monthly_cost_usd);
 
     // Create a metrics instance and simulate some operations
@@ -0,0 +1,273 @@
+//! Infeasibility diagnostics.
+//!
+//! When no stack satisfies a blueprint, [`Selector::diagnose`] looks for the
+//! smallest sets of constraint relaxations that make it feasible: raising the
+//! budget, allowing another region, dropping a requirement or removing an
+//! entry from `prefs.avoid`. Each suggestion comes with the plan the relaxed
+//! blueprint produces.
+
+use crate::schema::{Blueprint, ComplianceType, Relaxation};
+use crate::selector::Selector;
+use crate::util::combinations;
+
+/// Largest number of changes combined into one suggestion.
+const MAX_CHANGES: usize = 2;
+
+/// One way of loosening a blueprint.
+#[derive(Debug, Clone, PartialEq)]
+enum Change {
+    DropPersistence,
+    DropLanguageMode,
+    AllowRegion(String),
+    Unavoid {
+        category: String,
+        name: String,
+    },
+    DropCompliance(ComplianceType),
+    DropFloor(&'static str),
+    /// Raise the budget to the cheapest stack left by the other changes
+    RaiseBudget,
+}
+
+impl Change {
+    /// Apply the change, returning its description, or `None` when it does
+    /// not loosen this blueprint.
+    fn apply(&self, selector: &Selector, blueprint: &mut Blueprint) -> Option<String> {
+        let constraints = &mut blueprint.constraints;
+        match self {
+            Change::DropPersistence => {
+                let persistence = constraints.persistence.take()?;
+                Some(format!("Drop persistence: {}", persistence.as_str()))
+            }
+            Change::DropLanguageMode => {
+                let mode = blueprint.single_language_mode.take()?;
+                Some(format!("Drop single_language_mode: {}", mode.language()))
+            }
+            Change::AllowRegion(region) => {
+                constraints.region_allow.as_mut()?.push(region.clone());
+                Some(format!("Add '{region}' to region_allow"))
+            }
+            Change::Unavoid { category, name } => {
+                let avoided = blueprint.prefs.as_mut()?.avoid.get_mut(category)?;
+                avoided.retain(|n| n != name);
+                Some(format!("Remove '{name}' from prefs.avoid.{category}"))
+            }
+            Change::DropCompliance(compliance) => {
+                constraints.compliance.as_mut()?.retain(|c| c != compliance);
+                Some(format!("Drop compliance: {}", compliance.as_str()))
+            }
+            Change::DropFloor(name) => {
+                let floor = match *name {
+                    "quality_min" => &mut constraints.quality_min,
+                    "security_min" => &mut constraints.security_min,
+                    _ => &mut constraints.slo_min,
+                };
+                floor.take()?;
+                Some(format!("Drop {name}"))
+            }
+            Change::RaiseBudget => {
+                let current = constraints.monthly_cost_usd_max?;
+                let cheapest = selector.minimum_cost(blueprint).ok()?;
+                if cheapest <= current {
+                    return None;
+                }
+                // Round up to cents so the cheapest stack still fits
+                let raised = (cheapest * 100.0).ceil() / 100.0;
+                blueprint.constraints.monthly_cost_usd_max = Some(raised);
+                Some(format!(
+                    "Raise monthly_cost_usd_max from ${current} to ${raised}"
+                ))
+            }
+        }
+    }
+}
+
+/// Every change that loosens some constraint of the blueprint, with the
+/// budget last so it is raised against the other changes.
+fn candidate_changes(selector: &Selector, blueprint: &Blueprint) -> Vec<Change> {
+    let constraints = &blueprint.constraints;
+    let mut changes = Vec::new();
+
+    if constraints.persistence.is_some() {
+        changes.push(Change::DropPersistence);
+    }
+    if blueprint.single_language_mode.is_some() {
+        changes.push(Change::DropLanguageMode);
+    }
+    if let Some(allowed) = &constraints.region_allow {
+        let catalog = selector.region_catalog();
+        for region in selector.candidate_regions() {
+            if !allowed.iter().any(|a| catalog.overlaps(a, &region)) {
+                changes.push(Change::AllowRegion(region));
+            }
+        }
+    }
+    if let Some(prefs) = &blueprint.prefs {
+        for (category, names) in &prefs.avoid {
+            for name in names {
+                changes.push(Change::Unavoid {
+                    category: category.clone(),
+                    name: name.clone(),
+                });
+            }
+        }
+    }
+    for compliance in constraints.compliance.iter().flatten() {
+        changes.push(Change::DropCompliance(compliance.clone()));
+    }
+    let floors = [
+        ("quality_min", &constraints.quality_min),
+        ("security_min", &constraints.security_min),
+        ("slo_min", &constraints.slo_min),
+    ];
+    for (name, floor) in floors {
+        if floor.is_some() {
+            changes.push(Change::DropFloor(name));
+        }
+    }
+    if constraints.monthly_cost_usd_max.is_some() {
+        changes.push(Change::RaiseBudget);
+    }
+
+    changes
+}
+
+impl Selector {
+    /// Suggest the smallest sets of constraint relaxations that make the
+    /// blueprint feasible, each with the plan it would produce.
+    ///
+    /// Single changes are tried first; combinations of up to `MAX_CHANGES`
+    /// changes are only tried when no smaller set works. Feasible blueprints
+    /// and blueprints that no such relaxation fixes yield no suggestions.
+    pub fn diagnose(&self, blueprint: &Blueprint) -> Vec<Relaxation> {
+        if self.select(blueprint).is_ok() {
+            return Vec::new();
+        }
+
+        let changes = candidate_changes(self, blueprint);
+        for size in 1..=MAX_CHANGES.min(changes.len()) {
+            let mut relaxations = Vec::new();
+            for combination in combinations(changes.len(), size) {
+                let mut relaxed = blueprint.clone();
+                let described: Option<Vec<String>> = combination
+                    .iter()
+                    .map(|&i| changes[i].apply(self, &mut relaxed))
+                    .collect();
+                let Some(described) = described else {
+                    continue;
+                };
+                if let Ok(plan) = self.select(&relaxed) {
+                    relaxations.push(Relaxation {
+                        changes: described,
+                        plan,
+                    });
+                }
+            }
+            if !relaxations.is_empty() {
+                return relaxations;
+            }
+        }
+
+        Vec::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::validate_blueprint;
+
+    fn selector() -> Selector {
+        Selector::new(include_str!("../resources/rules.yaml"), 42, 8).unwrap()
+    }
+
+    fn blueprint(extra: &str) -> Blueprint {
+        validate_blueprint(&format!(
+            r#"
+project_name: "diagnose"
+goals: ["Build a web app"]
+traffic_profile:
+  rps_peak: 100
+  global: false
+  latency_sensitive: false
+{extra}
+"#
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_feasible_blueprint_has_no_suggestions() {
+        let selector = selector();
+        let blueprint = blueprint("constraints:\n  monthly_cost_usd_max: 500");
+        assert!(selector.diagnose(&blueprint).is_empty());
+    }
+
+    #[test]
+    fn test_budget_raised_to_cheapest_stack() {
+        let selector = selector();
+        let blueprint = blueprint("constraints:\n  monthly_cost_usd_max: 10");
+        assert!(selector.select(&blueprint).is_err());
+
+        let relaxations = selector.diagnose(&blueprint);
+        assert_eq!(relaxations.len(), 1);
+        let cheapest = selector.minimum_cost(&blueprint).unwrap();
+        let raised = (cheapest * 100.0).ceil() / 100.0;
+        assert_eq!(
+            relaxations[0].changes,
+            vec![format!("Raise monthly_cost_usd_max from $10 to ${raised}")]
+        );
+        assert!(relaxations[0].plan.estimated.monthly_cost_usd <= raised);
+    }
+
+    #[test]
+    fn test_single_changes_preferred() {
+        let selector = selector();
+        let blueprint = blueprint(
+            r#"constraints:
+  persistence: kv
+prefs:
+  avoid:
+    database: ["DynamoDB", "Redis"]"#,
+        );
+
+        let relaxations = selector.diagnose(&blueprint);
+        let changes: Vec<&str> = relaxations.iter().map(|r| r.changes[0].as_str()).collect();
+        assert_eq!(
+            changes,
+            vec![
+                "Drop persistence: kv",
+                "Remove 'DynamoDB' from prefs.avoid.database",
+                "Remove 'Redis' from prefs.avoid.database",
+            ]
+        );
+        assert_eq!(
+            relaxations[1].plan.stack.choice("database"),
+            Some("DynamoDB")
+        );
+        assert_eq!(relaxations[2].plan.stack.choice("database"), Some("Redis"));
+    }
+
+    #[test]
+    fn test_pairs_when_no_single_change_works() {
+        let selector = selector();
+        let blueprint = blueprint(
+            r#"constraints:
+  persistence: kv
+single_language_mode: rust
+prefs:
+  avoid:
+    database: ["DynamoDB", "Redis"]
+    backend: ["Actix Web", "Axum"]"#,
+        );
+
+        let relaxations = selector.diagnose(&blueprint);
+        assert_eq!(relaxations.len(), 9);
+        assert!(relaxations.iter().all(|r| r.changes.len() == 2));
+        assert!(relaxations.iter().any(|r| r.changes
+            == vec![
+                "Drop persistence: kv".to_string(),
+                "Drop single_language_mode: Rust".to_string(),
+            ]));
+    }
+}
@@ -0,0 +1,210 @@
+//! Error type shared by the library and the CLI.
+//!
+//! Every fallible public function returns a [`RuneforgeError`]. Callers can
+//! branch on the variant instead of the message, and the CLI derives its exit
+//! code from it.
+
+use crate::schema::Rejection;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum RuneforgeError {
+    /// The blueprint is not valid YAML or JSON, or has the wrong shape
+    #[error("Failed to parse blueprint: {message}")]
+    BlueprintParse { message: String },
+
+    /// The blueprint parsed but breaks a schema rule
+    #[error("Invalid blueprint{}: {message}", location(.path))]
+    BlueprintSchema {
+        /// JSON pointer to the offending value, e.g. `/traffic_profile/rps_peak`
+        path: String,
+        message: String,
+    },
+
+    /// A generated plan breaks the output schema
+    #[error("Output schema validation failed{}: {message}", location(.path))]
+    OutputSchema {
+        /// JSON pointer to the offending value, e.g. `/decisions/0/score`
+        path: String,
+        message: String,
+    },
+
+    /// Every candidate of a category was removed by the constraint filters
+    #[error("No suitable {category} candidates found{}", rejection_list(.rejections))]
+    NoSuitableCandidates {
+        category: String,
+        rejections: Vec<Rejection>,
+    },
+
+    /// Candidates exist for every category but no combination fits the budget
+    #[error("No stack found within cost constraint of ${budget}")]
+    BudgetExceeded { budget: f64 },
+
+    /// Candidates exist for every category but none combine into a stack
+    /// that meets their `requires`
+    #[error("No stack found whose choices meet each other's requirements")]
+    NoCompatibleStack,
+
+    /// The rules file could not be parsed or refers to something unknown
+    #[error("Failed to parse rules: {message}")]
+    Rules { message: String },
+
+    /// `rules lint` found errors, or warnings under `--strict`
+    #[error("{file} failed lint with {errors} error(s) and {warnings} warning(s)")]
+    RulesLint {
+        file: String,
+        errors: usize,
+        warnings: usize,
+    },
+
+    /// A checked-in JSON Schema no longer matches the Rust types
+    #[error("Schema {file} is out of date ({} difference{}); regenerate it with `runeforge schema emit`", .differences.len(), if .differences.len() == 1 { "" } else { "s" })]
+    SchemaDrift {
+        file: String,
+        differences: Vec<String>,
+    },
+
+    /// Reading or writing a file failed
+    #[error("Failed to {action} {path}: {source}")]
+    Io {
+        /// What was being done, e.g. `read rules file`
+        action: &'static str,
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
+}
+
+/// ` at <pointer>`, or nothing for the document root.
+pub(crate) fn location(path: &str) -> String {
+    if path.is_empty() {
+        String::new()
+    } else {
+        format!(" at {path}")
+    }
+}
+
+fn rejection_list(rejections: &[Rejection]) -> String {
+    if rejections.is_empty() {
+        return String::new();
+    }
+    let reasons: Vec<String> = rejections.iter().map(|r| r.to_string()).collect();
+    format!(" ({})", reasons.join("; "))
+}
+
+impl RuneforgeError {
+    /// Whether the blueprint is valid but no stack satisfies it.
+    pub fn is_infeasible(&self) -> bool {
+        matches!(
+            self,
+            RuneforgeError::NoSuitableCandidates { .. }
+                | RuneforgeError::BudgetExceeded { .. }
+                | RuneforgeError::NoCompatibleStack
+        )
+    }
+
+    /// CLI exit code: 1 for input errors, 2 for output schema errors and 3
+    /// when no stack satisfies the blueprint.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            RuneforgeError::OutputSchema { .. } => 2,
+            RuneforgeError::NoSuitableCandidates { .. }
+            | RuneforgeError::BudgetExceeded { .. }
+            | RuneforgeError::NoCompatibleStack => 3,
+            RuneforgeError::BlueprintParse { .. }
+            | RuneforgeError::BlueprintSchema { .. }
+            | RuneforgeError::Rules { .. }
+            | RuneforgeError::RulesLint { .. }
+            | RuneforgeError::SchemaDrift { .. }
+            | RuneforgeError::Io { .. } => 1,
+        }
+    }
+
+    pub(crate) fn blueprint_schema(path: &str, message: impl Into<String>) -> Self {
+        RuneforgeError::BlueprintSchema {
+            path: path.to_string(),
+            message: message.into(),
+        }
+    }
+
+    /// Error for a search that found no complete stack: over budget when
+    /// one is set, otherwise because no choices are compatible.
+    pub(crate) fn no_stack(budget: Option<f64>) -> Self {
+        match budget {
+            Some(budget) => RuneforgeError::BudgetExceeded { budget },
+            None => RuneforgeError::NoCompatibleStack,
+        }
+    }
+
+    pub(crate) fn output_schema(path: &str, message: impl Into<String>) -> Self {
+        RuneforgeError::OutputSchema {
+            path: path.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exit_codes_follow_variant() {
+        let infeasible = RuneforgeError::NoSuitableCandidates {
+            category: "database".to_string(),
+            rejections: vec![Rejection {
+                candidate: "Redis".to_string(),
+                filter: "persistence".to_string(),
+                required: "sql".to_string(),
+                actual: "kv".to_string(),
+            }],
+        };
+        assert_eq!(infeasible.exit_code(), 3);
+        assert!(infeasible.is_infeasible());
+        assert_eq!(
+            infeasible.to_string(),
+            "No suitable database candidates found (Redis: persistence (required sql, actual kv))"
+        );
+
+        let budget = RuneforgeError::BudgetExceeded { budget: 50.0 };
+        assert_eq!(budget.exit_code(), 3);
+        assert_eq!(
+            budget.to_string(),
+            "No stack found within cost constraint of $50"
+        );
+        assert_eq!(
+            RuneforgeError::no_stack(Some(50.0)).to_string(),
+            budget.to_string()
+        );
+
+        let incompatible = RuneforgeError::no_stack(None);
+        assert_eq!(incompatible.exit_code(), 3);
+        assert!(incompatible.is_infeasible());
+        assert_eq!(
+            incompatible.to_string(),
+            "No stack found whose choices meet each other's requirements"
+        );
+
+        let output = RuneforgeError::output_schema("/decisions/0/score", "bad");
+        assert_eq!(output.exit_code(), 2);
+        assert_eq!(
+            output.to_string(),
+            "Output schema validation failed at /decisions/0/score: bad"
+        );
+        assert_eq!(
+            RuneforgeError::blueprint_schema("/goals", "goals cannot be empty").exit_code(),
+            1
+        );
+        let io = RuneforgeError::Io {
+            action: "read rules file",
+            path: "rules.yaml".to_string(),
+            source: std::io::Error::new(std::io::ErrorKind::NotFound, "not found"),
+        };
+        assert_eq!(io.exit_code(), 1);
+        assert!(!io.is_infeasible());
+        assert_eq!(
+            io.to_string(),
+            "Failed to read rules file rules.yaml: not found"
+        );
+    }
+}
@@ -0,0 +1,304 @@
+//! Layered rules files.
+//!
+//! A rules file may build on others with `extends: base.yaml` (or a list of
+//! paths, relative to the file), and several files can be stacked with
+//! repeated `--rules` flags. Later layers are merged onto earlier ones:
+//!
+//! - mappings merge key by key, and a `null` value removes the key, so an
+//!   overlay can set `weights: { cost: 0.3 }` or drop a region;
+//! - lists of named entries, such as candidate lists, merge by `name`: a
+//!   matching entry overrides the fields it sets, a new name is appended and
+//!   `{ name: X, remove: true }` removes `X`;
+//! - anything else, including lists of strings, is replaced.
+
+use crate::error::RuneforgeError;
+use crate::selector::Rules;
+use serde_yaml::{Mapping, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const EXTENDS: &str = "extends";
+
+/// Merge `overlay` onto `base` using the layering rules above.
+pub fn merge(base: &mut Value, overlay: Value) -> Result<(), RuneforgeError> {
+    merge_at(base, overlay, "")
+}
+
+fn merge_at(base: &mut Value, overlay: Value, path: &str) -> Result<(), RuneforgeError> {
+    match (base, overlay) {
+        (Value::Mapping(base), Value::Mapping(overlay)) => {
+            for (key, value) in overlay {
+                let child = format!("{path}/{}", key_name(&key));
+                if value.is_null() {
+                    base.remove(&key);
+                } else if let Some(existing) = base.get_mut(&key) {
+                    merge_at(existing, value, &child)?;
+                } else {
+                    base.insert(key, value);
+                }
+            }
+            Ok(())
+        }
+        (Value::Sequence(base), Value::Sequence(overlay))
+            if is_named(base) && is_named(&overlay) =>
+        {
+            for item in overlay {
+                let name = entry_name(&item).unwrap_or_default().to_string();
+                let position = base.iter().position(|b| entry_name(b) == Some(&name));
+                if item.get("remove") == Some(&Value::Bool(true)) {
+                    let Some(position) = position else {
+                        return Err(RuneforgeError::Rules {
+                            message: format!("cannot remove unknown entry '{name}' from {path}"),
+                        });
+                    };
+                    base.remove(position);
+                } else if let Some(position) = position {
+                    merge_at(&mut base[position], item, &format!("{path}/{name}"))?;
+                } else {
+                    base.push(item);
+                }
+            }
+            Ok(())
+        }
+        (base, overlay) => {
+            *base = overlay;
+            Ok(())
+        }
+    }
+}
+
+fn key_name(key: &Value) -> String {
+    match key {
+        Value::String(s) => s.clone(),
+        other => serde_yaml::to_string(other)
+            .unwrap_or_default()
+            .trim()
+            .to_string(),
+    }
+}
+
+fn entry_name(item: &Value) -> Option<&str> {
+    item.get("name").and_then(Value::as_str)
+}
+
+/// Whether every item is a mapping with a string `name`.
+fn is_named(items: &[Value]) -> bool {
+    items.iter().all(|item| entry_name(item).is_some())
+}
+
+fn parse(source: &str, origin: &str) -> Result<Value, RuneforgeError> {
+    serde_yaml::from_str(source).map_err(|e| RuneforgeError::Rules {
+        message: format!("{origin}: {e}"),
+    })
+}
+
+/// Paths listed under `extends`, removing the directive from the document.
+fn take_extends(document: &mut Value, origin: &str) -> Result<Vec<String>, RuneforgeError> {
+    let Some(mapping) = document.as_mapping_mut() else {
+        return Ok(Vec::new());
+    };
+    let invalid = || RuneforgeError::Rules {
+        message: format!("{origin}: extends must be a path or a list of paths"),
+    };
+    match mapping.remove(EXTENDS) {
+        None => Ok(Vec::new()),
+        Some(Value::String(path)) => Ok(vec![path]),
+        Some(Value::Sequence(paths)) => paths
+            .into_iter()
+            .map(|p| p.as_str().map(str::to_string).ok_or_else(invalid))
+            .collect(),
+        Some(_) => Err(invalid()),
+    }
+}
+
+/// Whether a rules source builds on other files with `extends`.
+pub fn has_extends(source: &str) -> bool {
+    serde_yaml::from_str::<Value>(source)
+        .ok()
+        .and_then(|v| v.get(EXTENDS).cloned())
+        .is_some()
+}
+
+/// Merge rules sources given as YAML strings, in order.
+///
+/// `extends` needs a file to resolve paths against, so it is rejected here;
+/// use [`resolve_files`] for files on disk.
+pub fn merge_sources(sources: &[&str]) -> Result<Value, RuneforgeError> {
+    let mut merged = Value::Mapping(Mapping::new());
+    for (index, source) in sources.iter().enumerate() {
+        let origin = format!("rules layer {}", index + 1);
+        let mut layer = parse(source, &origin)?;
+        if !take_extends(&mut layer, &origin)?.is_empty() {
+            return Err(RuneforgeError::Rules {
+                message: format!("{origin}: extends is only supported in rules files"),
+            });
+        }
+        merge(&mut merged, layer)?;
+    }
+    Ok(merged)
+}
+
+/// Load rules files in order, following `extends`, and merge them.
+pub fn resolve_files<P: AsRef<Path>>(paths: &[P]) -> Result<Value, RuneforgeError> {
+    let mut merged = Value::Mapping(Mapping::new());
+    for path in paths {
+        let layer = load_file(path.as_ref(), &mut Vec::new())?;
+        merge(&mut merged, layer)?;
+    }
+    Ok(merged)
+}
+
+/// Load one file with everything it extends merged underneath it.
+fn load_file(path: &Path, chain: &mut Vec<PathBuf>) -> Result<Value, RuneforgeError> {
+    let origin = path.display().to_string();
+    let source = fs::read_to_string(path).map_err(|source| RuneforgeError::Io {
+        action: "read rules file",
+        path: origin.clone(),
+        source,
+    })?;
+    let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    if chain.contains(&canonical) {
+        return Err(RuneforgeError::Rules {
+            message: format!("{origin} extends itself"),
+        });
+    }
+
+    let mut layer = parse(&source, &origin)?;
+    let extends = take_extends(&mut layer, &origin)?;
+    if extends.is_empty() {
+        return Ok(layer);
+    }
+
+    chain.push(canonical);
+    let directory = path.parent().unwrap_or(Path::new(""));
+    let mut merged = Value::Mapping(Mapping::new());
+    for base in extends {
+        let base = load_file(&directory.join(base), chain)?;
+        merge(&mut merged, base)?;
+    }
+    chain.pop();
+    merge(&mut merged, layer)?;
+    Ok(merged)
+}
+
+/// Deserialize merged rules.
+pub fn into_rules(merged: Value) -> Result<Rules, RuneforgeError> {
+    serde_yaml::from_value(merged).map_err(|e| RuneforgeError::Rules {
+        message: e.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const RULES: &str = include_str!("../resources/rules.yaml");
+
+    fn names(rules: &Rules, category: &str) -> Vec<String> {
+        rules
+            .candidates
+            .get(category)
+            .iter()
+            .map(|c| c.name.clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_overlay_overrides_adds_and_removes() {
+        let overlay = r#"
+weights: { cost: 0.3, ops: 0.0 }
+candidates:
+  database:
+    - name: PostgreSQL
+      monthly_cost_base: 1
+    - name: MongoDB
+      remove: true
+    - name: InternalDB
+      persistence: sql
+      metrics: { quality: 0.7, slo: 0.7, cost: 0.9, security: 0.8, ops: 0.6 }
+      regions: ["*"]
+regions:
+  apac: null
+"#;
+        let base: Rules = serde_yaml::from_str(RULES).unwrap();
+        let rules = into_rules(merge_sources(&[RULES, overlay]).unwrap()).unwrap();
+
+        assert_eq!(rules.weights.cost, 0.3);
+        assert_eq!(rules.weights.ops, 0.0);
+        assert_eq!(rules.weights.quality, base.weights.quality);
+
+        let mut expected = names(&base, "database");
+        expected.retain(|n| n != "MongoDB");
+        expected.push("InternalDB".to_string());
+        assert_eq!(names(&rules, "database"), expected);
+
+        let postgres = rules
+            .candidates
+            .get("database")
+            .iter()
+            .find(|c| c.name == "PostgreSQL")
+            .unwrap();
+        assert_eq!(postgres.monthly_cost_base, 1.0);
+        assert_eq!(postgres.persistence.as_deref(), Some("sql"));
+        assert!(rules.regions.get("apac").is_none());
+        assert!(rules.regions.get("eu").is_some());
+    }
+
+    #[test]
+    fn test_lists_of_strings_are_replaced() {
+        let mut base: Value = serde_yaml::from_str("regions: [a, b]\nname: x").unwrap();
+        merge(&mut base, serde_yaml::from_str("regions: [c]").unwrap()).unwrap();
+        assert_eq!(
+            base,
+            serde_yaml::from_str::<Value>("regions: [c]\nname: x").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_removing_unknown_entry_fails() {
+        let overlay = "candidates:\n  cache:\n    - { name: Memcached2, remove: true }";
+        let err = merge_sources(&[RULES, overlay]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Failed to parse rules: cannot remove unknown entry 'Memcached2' from /candidates/cache"
+        );
+    }
+
+    #[test]
+    fn test_extends_resolves_relative_paths() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir(dir.path().join("org")).unwrap();
+        fs::write(dir.path().join("org/base.yaml"), RULES).unwrap();
+        fs::write(
+            dir.path().join("team.yaml"),
+            "extends: org/base.yaml\nweights: { quality: 0.4, ops: 0.0 }\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("local.yaml"),
+            "weights: { ops: 0.05, cost: 0.15 }\n",
+        )
+        .unwrap();
+
+        let merged =
+            resolve_files(&[dir.path().join("team.yaml"), dir.path().join("local.yaml")]).unwrap();
+        assert!(merged.get(EXTENDS).is_none());
+        let rules = into_rules(merged).unwrap();
+        assert_eq!(rules.weights.quality, 0.4);
+        assert_eq!(rules.weights.ops, 0.05);
+        assert_eq!(rules.weights.cost, 0.15);
+    }
+
+    #[test]
+    fn test_extends_cycles_and_strings() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("a.yaml"), "extends: b.yaml\n").unwrap();
+        fs::write(dir.path().join("b.yaml"), "extends: [a.yaml]\n").unwrap();
+        let err = resolve_files(&[dir.path().join("a.yaml")]).unwrap_err();
+        assert!(err.to_string().contains("a.yaml extends itself"));
+
+        let err = merge_sources(&["extends: base.yaml"]).unwrap_err();
+        assert!(err.to_string().contains("only supported in rules files"));
+    }
+}
@@ -10,13 +10,29 @@ pub mod adapters;
 
 mod check_forbidden_apis;
 
+#[cfg(feature = "std")]
+pub mod diagnostics;
+#[cfg(feature = "std")]
+pub mod error;
+#[cfg(feature = "std")]
+pub mod layers;
+#[cfg(feature = "std")]
+pub mod lint;
 #[cfg(feature = "std")]
 pub mod metrics_handler;
 #[cfg(feature = "std")]
 pub mod observability;
 #[cfg(feature = "std")]
+pub mod pricing;
+#[cfg(feature = "std")]
+pub mod regions;
+#[cfg(feature = "std")]
+pub mod rules_source;
+#[cfg(feature = "std")]
 pub mod schema;
 #[cfg(feature = "std")]
+pub mod scoring;
+#[cfg(feature = "std")]
 pub mod selector;
 #[cfg(feature = "std")]
 pub mod util;
@@ -24,6 +40,9 @@ pub mod util;
 #[cfg(test)]
 mod test_utils;
 
+#[cfg(feature = "std")]
+pub use error::RuneforgeError;
+
 pub fn is_wasm() -> bool {
     cfg!(target_arch = "wasm32")
 }
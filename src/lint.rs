@@ -0,0 +1,955 @@
+//! Rules file linting.
+//!
+//! [`Rules::validate`] checks a parsed rules file for mistakes serde accepts:
+//! metrics outside `[0, 1]`, weights that do not sum to 1, adjustments and
+//! priorities naming unknown metrics, categories that cannot be ordered,
+//! duplicate candidate names, requirements nothing satisfies and unknown
+//! regions.
+//! [`lint_rules`] also checks the source against the rules JSON Schema and
+//! attaches line numbers to every finding.
+
+use crate::error::{location, RuneforgeError};
+use crate::regions::is_wildcard;
+use crate::schema::rules_schema_violations;
+use crate::scoring::ScoringStrategy;
+use crate::selector::{Candidate, Cardinality, Metrics, Rules, BUILTIN_METRICS};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+
+/// Tolerance when checking that the weights sum to 1.
+const WEIGHT_SUM_TOLERANCE: f64 = 1e-6;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// The rules do not behave as written
+    Error,
+    /// The rules work but probably not as intended
+    Warning,
+}
+
+/// One problem found in a rules file.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Finding {
+    pub severity: Severity,
+    /// JSON pointer to the offending value, e.g. `/candidates/database/2/name`
+    pub path: String,
+    /// 1-based line in the rules source, when it could be located
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl Finding {
+    fn error(path: impl Into<String>, message: impl Into<String>) -> Self {
+        Finding {
+            severity: Severity::Error,
+            path: path.into(),
+            line: None,
+            message: message.into(),
+        }
+    }
+
+    fn warning(path: impl Into<String>, message: impl Into<String>) -> Self {
+        Finding {
+            severity: Severity::Warning,
+            ..Finding::error(path, message)
+        }
+    }
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        write!(f, "{severity}{}: {}", location(&self.path), self.message)
+    }
+}
+
+impl Rules {
+    /// Semantic problems serde does not catch, errors first in file order
+    /// within each check.
+    pub fn validate(&self) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        self.check_weights(&mut findings);
+        self.check_dimensions(&mut findings);
+        self.check_adjustments(&mut findings);
+        self.check_default_services(&mut findings);
+        self.check_scoring(&mut findings);
+        self.check_penalties(&mut findings);
+        self.check_categories(&mut findings);
+        self.check_candidates(&mut findings);
+        self.check_compliance(&mut findings);
+        self.check_regions(&mut findings);
+        findings
+    }
+
+    fn check_weights(&self, findings: &mut Vec<Finding>) {
+        let weights: Vec<(String, f64)> = self
+            .weights
+            .named()
+            .into_iter()
+            .map(|(name, weight)| (format!("/weights/{name}"), weight))
+            .chain(
+                self.dimensions
+                    .iter()
+                    .enumerate()
+                    .map(|(index, d)| (format!("/dimensions/{index}/weight"), d.weight)),
+            )
+            .collect();
+        for (path, weight) in &weights {
+            if *weight < 0.0 {
+                findings.push(Finding::error(
+                    path,
+                    format!("weight is {weight}; weights cannot be negative"),
+                ));
+            }
+        }
+        let sum: f64 = weights.iter().map(|(_, w)| w).sum();
+        if (sum - 1.0).abs() > WEIGHT_SUM_TOLERANCE {
+            findings.push(Finding::warning(
+                "/weights",
+                format!("weights sum to {sum}, not 1, so scores are skewed"),
+            ));
+        }
+    }
+
+    fn check_dimensions(&self, findings: &mut Vec<Finding>) {
+        let names = self.dimensions.iter().map(|d| d.name.as_str());
+        check_duplicates("/dimensions", "dimension", names, findings);
+
+        for (index, dimension) in self.dimensions.iter().enumerate() {
+            let path = format!("/dimensions/{index}");
+            if BUILTIN_METRICS.contains(&dimension.name.as_str()) {
+                findings.push(Finding::error(
+                    format!("{path}/name"),
+                    format!(
+                        "'{}' is a built-in metric; set its weight under weights",
+                        dimension.name
+                    ),
+                ));
+            }
+            if !(0.0..=1.0).contains(&dimension.default) {
+                findings.push(Finding::error(
+                    format!("{path}/default"),
+                    format!(
+                        "default is {}; metrics must be between 0 and 1",
+                        dimension.default
+                    ),
+                ));
+            }
+        }
+    }
+
+    fn check_adjustments(&self, findings: &mut Vec<Finding>) {
+        for (index, adjustment) in self.adjustments.iter().enumerate() {
+            let path = format!("/adjustments/{index}");
+            if !self.is_metric(&adjustment.metric) {
+                findings.push(Finding::error(
+                    format!("{path}/metric"),
+                    format!("unknown metric '{}'", adjustment.metric),
+                ));
+            }
+            if adjustment.when.is_empty() {
+                findings.push(Finding::warning(
+                    format!("{path}/when"),
+                    "no conditions, so the adjustment always applies; change the weight instead",
+                ));
+            }
+        }
+    }
+
+    fn check_default_services(&self, findings: &mut Vec<Finding>) {
+        let names = self.default_services.iter().map(|s| s.name.as_str());
+        check_duplicates("/default_services", "service", names, findings);
+
+        for (index, service) in self.default_services.iter().enumerate() {
+            let Some(workload) = service.workload else {
+                continue;
+            };
+            let candidates = self.candidates.services.get(service.kind);
+            if !candidates.is_empty()
+                && candidates
+                    .iter()
+                    .all(|c| !c.workloads.is_empty() && !c.workloads.contains(&workload))
+            {
+                findings.push(Finding::warning(
+                    format!("/default_services/{index}/workload"),
+                    format!(
+                        "no {} candidate runs {} workloads, so the {} service is never planned",
+                        service.kind.as_str(),
+                        workload.as_str(),
+                        service.name
+                    ),
+                ));
+            }
+        }
+    }
+
+    fn check_scoring(&self, findings: &mut Vec<Finding>) {
+        let scoring = &self.scoring;
+        if !scoring.priority.is_empty() && scoring.strategy != ScoringStrategy::Lexicographic {
+            findings.push(Finding::warning(
+                "/scoring/priority",
+                format!(
+                    "priority only applies to lexicographic scoring, not {}",
+                    scoring.strategy
+                ),
+            ));
+        }
+        for (index, metric) in scoring.priority.iter().enumerate() {
+            if !self.is_metric(metric) {
+                findings.push(Finding::error(
+                    format!("/scoring/priority/{index}"),
+                    format!("unknown metric '{metric}'"),
+                ));
+            }
+        }
+    }
+
+    /// Whether `name` is a built-in metric or a declared dimension.
+    fn is_metric(&self, name: &str) -> bool {
+        BUILTIN_METRICS.contains(&name) || self.dimensions.iter().any(|d| d.name == name)
+    }
+
+    fn check_penalties(&self, findings: &mut Vec<Finding>) {
+        let extra_store = self.polyglot_penalties.extra_store;
+        if !(0.0..=1.0).contains(&extra_store) {
+            findings.push(Finding::error(
+                "/polyglot_penalties/extra_store",
+                format!("extra_store is {extra_store}; it must be between 0 and 1"),
+            ));
+        }
+    }
+
+    fn check_categories(&self, findings: &mut Vec<Finding>) {
+        let before = findings.len();
+        let names = self.categories.iter().map(|c| c.name.as_str());
+        check_duplicates("/categories", "category", names, findings);
+
+        for (index, category) in self.categories.iter().enumerate() {
+            let path = format!("/categories/{index}");
+            for (position, dependency) in category.depends_on.iter().enumerate() {
+                if !self.categories.iter().any(|c| &c.name == dependency) {
+                    findings.push(Finding::error(
+                        format!("{path}/depends_on/{position}"),
+                        format!("unknown category '{dependency}'"),
+                    ));
+                }
+            }
+            if category.count.is_some() && category.cardinality != Cardinality::Many {
+                findings.push(Finding::warning(
+                    format!("{path}/count"),
+                    "count only applies to categories with cardinality many",
+                ));
+            }
+            let Some(needed) = &category.needed else {
+                continue;
+            };
+            if category.name == "language" {
+                findings.push(Finding::warning(
+                    format!("{path}/needed"),
+                    "language is always needed, so needed is ignored",
+                ));
+            }
+            for (position, condition) in needed.when.iter().enumerate() {
+                if condition.is_empty() {
+                    findings.push(Finding::warning(
+                        format!("{path}/needed/when/{position}"),
+                        "no conditions, so the category is always needed",
+                    ));
+                }
+            }
+        }
+        if findings.len() == before {
+            if let Err(e) = self.category_order() {
+                let RuneforgeError::Rules { message } = e else {
+                    unreachable!("category_order only fails with rules errors")
+                };
+                findings.push(Finding::error("/categories", message));
+            }
+        }
+
+        let language = self.categories.iter().find(|c| c.name == "language");
+        if language.map(|c| c.cardinality) != Some(Cardinality::One) {
+            findings.push(Finding::error(
+                "/categories",
+                "rules need a 'language' category with cardinality one",
+            ));
+        }
+
+        for name in self.candidates.categories.keys() {
+            if !self.categories.iter().any(|c| &c.name == name) {
+                findings.push(Finding::warning(
+                    format!("/candidates/{name}"),
+                    format!(
+                        "'{name}' is not a declared category, so its candidates are never selected"
+                    ),
+                ));
+            }
+        }
+    }
+
+    fn check_candidates(&self, findings: &mut Vec<Finding>) {
+        for category in &self.categories {
+            let base = format!("/candidates/{}", category.name);
+            if self.candidates.get(&category.name).is_empty()
+                && category.cardinality != Cardinality::Optional
+            {
+                findings.push(Finding::warning(
+                    base,
+                    format!("no {} candidates, so every plan fails", category.name),
+                ));
+            }
+        }
+
+        for (name, candidates) in self.candidate_lists() {
+            let base = format!("/candidates/{name}");
+            let names = candidates.iter().map(|c| c.name.as_str());
+            check_duplicates(&base, "candidate", names, findings);
+            let category = self.categories.iter().find(|c| c.name == name);
+
+            for (index, candidate) in candidates.iter().enumerate() {
+                let path = format!("{base}/{index}");
+                self.check_metrics(&path, &candidate.metrics, findings);
+                check_cost(&path, candidate.monthly_cost_base, findings);
+                let Some(category) = category else {
+                    continue;
+                };
+                for (dependency, required) in candidate.requires.iter().flat_map(|r| &r.0) {
+                    let path = format!("{path}/requires/{dependency}");
+                    if !category.depends_on.contains(dependency) {
+                        findings.push(Finding::error(
+                            path,
+                            format!(
+                                "'{}' requires {dependency} '{required}', but {name} does not depend on {dependency}",
+                                candidate.name
+                            ),
+                        ));
+                    } else if !self
+                        .candidates
+                        .get(dependency)
+                        .iter()
+                        .any(|c| &c.name == required)
+                    {
+                        findings.push(Finding::error(
+                            path,
+                            format!(
+                                "'{}' requires {dependency} '{required}', which is not a {dependency} candidate",
+                                candidate.name
+                            ),
+                        ));
+                    }
+                }
+            }
+        }
+
+        for (kind, candidates) in self.candidates.services.by_kind() {
+            let base = format!("/candidates/services/{kind}");
+            let names = candidates.iter().map(|c| c.name.as_str());
+            check_duplicates(&base, "candidate", names, findings);
+
+            for (index, candidate) in candidates.iter().enumerate() {
+                let path = format!("{base}/{index}");
+                self.check_metrics(&path, &candidate.metrics, findings);
+                check_cost(&path, candidate.monthly_cost_base, findings);
+            }
+        }
+    }
+
+    fn check_metrics(&self, path: &str, metrics: &Metrics, findings: &mut Vec<Finding>) {
+        for (name, value) in metrics.named() {
+            if !(0.0..=1.0).contains(&value) {
+                findings.push(Finding::error(
+                    format!("{path}/metrics/{name}"),
+                    format!("{name} is {value}; metrics must be between 0 and 1"),
+                ));
+            }
+        }
+        for name in metrics.dimensions.keys() {
+            if !self.dimensions.iter().any(|d| &d.name == name) {
+                findings.push(Finding::warning(
+                    format!("{path}/metrics/{name}"),
+                    format!("'{name}' is not a declared dimension, so it is not scored"),
+                ));
+            }
+        }
+    }
+
+    /// Every candidate list: declared categories in declaration order, then
+    /// lists no category declares.
+    fn candidate_lists(&self) -> Vec<(&str, &[Candidate])> {
+        let declared = self
+            .categories
+            .iter()
+            .map(|c| (c.name.as_str(), self.candidates.get(&c.name)));
+        let undeclared = self
+            .candidates
+            .categories
+            .iter()
+            .filter(|(name, _)| !self.categories.iter().any(|c| &c.name == *name))
+            .map(|(name, candidates)| (name.as_str(), candidates.as_slice()));
+        declared.chain(undeclared).collect()
+    }
+
+    fn check_compliance(&self, findings: &mut Vec<Finding>) {
+        let categories: Vec<(&str, &[Candidate])> = self
+            .categories
+            .iter()
+            .map(|c| (c.name.as_str(), self.candidates.get(&c.name)))
+            .collect();
+        let mut keys: Vec<&String> = self.compliance_requirements.keys().collect();
+        keys.sort();
+
+        for key in keys {
+            let requirement = &self.compliance_requirements[key];
+            let base = format!("/compliance_requirements/{key}");
+            for (index, category) in requirement.applies_to.iter().enumerate() {
+                if !categories.iter().any(|(name, _)| name == category) {
+                    findings.push(Finding::error(
+                        format!("{base}/applies_to/{index}"),
+                        format!("unknown category '{category}'"),
+                    ));
+                }
+            }
+
+            for (index, feature) in requirement.required_features.iter().enumerate() {
+                for (category, candidates) in &categories {
+                    let applies = requirement.applies_to.is_empty()
+                        || requirement.applies_to.iter().any(|c| c == category);
+                    if applies && !candidates.iter().any(|c| c.features.contains(feature)) {
+                        findings.push(Finding::warning(
+                            format!("{base}/required_features/{index}"),
+                            format!(
+                                "no {category} candidate provides '{feature}', so {key} can never be satisfied"
+                            ),
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    fn check_regions(&self, findings: &mut Vec<Finding>) {
+        let catalog = &self.regions;
+        if catalog.is_empty() {
+            return;
+        }
+
+        let mut names = catalog.names();
+        names.sort();
+        for name in names {
+            let parent = catalog.get(name).and_then(|r| r.parent.as_deref());
+            if let Some(parent) = parent {
+                if catalog.get(parent).is_none() {
+                    findings.push(Finding::error(
+                        format!("/regions/{name}/parent"),
+                        format!("unknown parent region '{parent}'"),
+                    ));
+                }
+            }
+        }
+
+        let candidates = self
+            .candidate_lists()
+            .into_iter()
+            .flat_map(|(category, candidates)| {
+                let base = format!("/candidates/{category}");
+                candidates
+                    .iter()
+                    .enumerate()
+                    .map(move |(index, c)| (format!("{base}/{index}"), &c.regions))
+            });
+        let services =
+            self.candidates
+                .services
+                .by_kind()
+                .into_iter()
+                .flat_map(|(kind, candidates)| {
+                    let base = format!("/candidates/services/{kind}");
+                    candidates
+                        .iter()
+                        .enumerate()
+                        .map(move |(index, c)| (format!("{base}/{index}"), &c.regions))
+                });
+        for (path, regions) in candidates.chain(services) {
+            for (index, region) in regions.iter().enumerate() {
+                if !is_wildcard(region) && !catalog.is_known(region) {
+                    findings.push(Finding::warning(
+                        format!("{path}/regions/{index}"),
+                        format!("unknown region '{region}'; only exact matches apply"),
+                    ));
+                }
+            }
+        }
+    }
+}
+
+fn check_cost(path: &str, cost: f64, findings: &mut Vec<Finding>) {
+    if cost < 0.0 {
+        findings.push(Finding::error(
+            format!("{path}/monthly_cost_base"),
+            format!("monthly_cost_base is {cost}; costs cannot be negative"),
+        ));
+    }
+}
+
+fn check_duplicates<'a>(
+    base: &str,
+    kind: &str,
+    names: impl Iterator<Item = &'a str>,
+    findings: &mut Vec<Finding>,
+) {
+    let mut seen: HashMap<&str, usize> = HashMap::new();
+    for (index, name) in names.enumerate() {
+        if let Some(first) = seen.insert(name, index) {
+            seen.insert(name, first);
+            findings.push(Finding::error(
+                format!("{base}/{index}/name"),
+                format!("duplicate {kind} '{name}' (first defined at {base}/{first})"),
+            ));
+        }
+    }
+}
+
+/// Lint a rules file: check it against the rules schema, then run
+/// [`Rules::validate`], and attach source lines to the findings.
+///
+/// Semantic findings are skipped for values the schema already rejected.
+/// Only YAML that cannot be parsed at all is an `Err`.
+pub fn lint_rules(source: &str) -> Result<Vec<Finding>, RuneforgeError> {
+    let value: Value = serde_yaml::from_str(source).map_err(|e| RuneforgeError::Rules {
+        message: e.to_string(),
+    })?;
+
+    let (violations, unknown_fields) = rules_schema_violations(&value);
+    let mut findings: Vec<Finding> = violations
+        .into_iter()
+        .map(|v| Finding::error(v.path, v.message))
+        .chain(
+            unknown_fields
+                .into_iter()
+                .map(|v| Finding::warning(v.path, format!("unknown field: {}", v.message))),
+        )
+        .collect();
+
+    match serde_yaml::from_str::<Rules>(source) {
+        Ok(rules) => {
+            for finding in rules.validate() {
+                let rejected = findings
+                    .iter()
+                    .any(|f| f.severity == Severity::Error && f.path == finding.path);
+                if !rejected {
+                    findings.push(finding);
+                }
+            }
+        }
+        // The schema normally explains why; fall back to serde's message
+        Err(e) if findings.iter().all(|f| f.severity != Severity::Error) => {
+            findings.push(Finding::error("", e.to_string()));
+        }
+        Err(_) => {}
+    }
+
+    for finding in &mut findings {
+        finding.line = locate_line(source, &finding.path);
+    }
+    findings.sort_by_key(|f| f.line.unwrap_or(0));
+    Ok(findings)
+}
+
+/// 1-based line of the value at a JSON pointer in block-style YAML.
+///
+/// Values inside flow collections (`{ ... }`, `[ ... ]`) resolve to the line
+/// of the nearest enclosing block value.
+fn locate_line(source: &str, pointer: &str) -> Option<usize> {
+    let lines: Vec<&str> = source.lines().collect();
+    let mut found = None;
+    let mut indent = None;
+    for segment in pointer.split('/').skip(1) {
+        let segment = segment.replace("~1", "/").replace("~0", "~");
+        let start = found.unwrap_or(0);
+        let next = match segment.parse::<usize>() {
+            Ok(index) => find_item(&lines, start, indent, index),
+            Err(_) => find_key(&lines, start, indent, &segment),
+        };
+        let Some((line, column)) = next else {
+            break;
+        };
+        found = Some(line);
+        indent = Some(column);
+    }
+    found.map(|line| line + 1)
+}
+
+/// Leading spaces of a line, or `None` for blank lines and comments.
+fn leading_spaces(line: &str) -> Option<usize> {
+    let trimmed = line.trim_start_matches(' ');
+    if trimmed.is_empty() || trimmed.starts_with('#') {
+        None
+    } else {
+        Some(line.len() - trimmed.len())
+    }
+}
+
+/// Line and column of key `key` in the mapping below `start`, whose parent
+/// sits at column `indent`.
+fn find_key(
+    lines: &[&str],
+    start: usize,
+    indent: Option<usize>,
+    key: &str,
+) -> Option<(usize, usize)> {
+    let mut child_column = None;
+    for (number, line) in lines.iter().enumerate().skip(start) {
+        let Some(leading) = leading_spaces(line) else {
+            continue;
+        };
+        if number > start && indent.is_some_and(|i| leading <= i) {
+            return None;
+        }
+        // Keys may follow sequence dashes: `  - name: x`
+        let mut column = leading;
+        let mut rest = &line[leading..];
+        while let Some(after) = rest.strip_prefix("- ") {
+            let trimmed = after.trim_start_matches(' ');
+            column += rest.len() - trimmed.len();
+            rest = trimmed;
+        }
+        if number == start && indent.is_some_and(|i| column <= i) {
+            continue;
+        }
+        let column_expected = *child_column.get_or_insert(column);
+        if column != column_expected {
+            continue;
+        }
+        let quoted = [key.to_string(), format!("\"{key}\""), format!("'{key}'")];
+        if quoted.iter().any(|k| {
+            rest.strip_prefix(k.as_str())
+                .is_some_and(|r| r.starts_with(':'))
+        }) {
+            return Some((number, column));
+        }
+    }
+    None
+}
+
+/// Line and column of item `index` in the sequence below `start`, whose
+/// parent sits at column `indent`.
+fn find_item(
+    lines: &[&str],
+    start: usize,
+    indent: Option<usize>,
+    index: usize,
+) -> Option<(usize, usize)> {
+    let mut item_column = None;
+    let mut count = 0;
+    for (number, line) in lines.iter().enumerate().skip(start + 1) {
+        let Some(leading) = leading_spaces(line) else {
+            continue;
+        };
+        let is_item = line[leading..] == *"-" || line[leading..].starts_with("- ");
+        if indent.is_some_and(|i| leading < i || (leading == i && !is_item)) {
+            return None;
+        }
+        if !is_item || *item_column.get_or_insert(leading) != leading {
+            continue;
+        }
+        if count == index {
+            return Some((number, leading));
+        }
+        count += 1;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::Workload;
+    use crate::selector::Requirements;
+
+    const RULES: &str = include_str!("../resources/rules.yaml");
+
+    fn messages(findings: &[Finding]) -> Vec<String> {
+        findings.iter().map(|f| f.to_string()).collect()
+    }
+
+    #[test]
+    fn test_shipped_rules_are_clean() {
+        let findings = lint_rules(RULES).unwrap();
+        assert!(findings.is_empty(), "{:?}", messages(&findings));
+    }
+
+    #[test]
+    fn test_validate_reports_semantic_problems() {
+        let mut rules: Rules = serde_yaml::from_str(RULES).unwrap();
+        rules.weights.ops = 0.2;
+        let candidates = &mut rules.candidates.categories;
+        candidates.get_mut("database").unwrap()[1].metrics.quality = 1.5;
+        let cache = candidates["cache"][0].name.clone();
+        candidates.get_mut("cache").unwrap()[1].name = cache;
+        candidates.get_mut("backend").unwrap()[0].requires = Some(Requirements(
+            [("language".to_string(), "Cobol".to_string())].into(),
+        ));
+        rules
+            .compliance_requirements
+            .get_mut("sbom")
+            .unwrap()
+            .required_features
+            .push("attestation".to_string());
+
+        let findings = rules.validate();
+        let cache = &rules.candidates.get("cache")[0].name;
+        let backend = &rules.candidates.get("backend")[0].name;
+        assert_eq!(
+            messages(&findings),
+            vec![
+                "warning at /weights: weights sum to 1.1, not 1, so scores are skewed".to_string(),
+                format!(
+                    "error at /candidates/backend/0/requires/language: '{backend}' requires language 'Cobol', which is not a language candidate"
+                ),
+                "error at /candidates/database/1/metrics/quality: quality is 1.5; metrics must be between 0 and 1".to_string(),
+                format!(
+                    "error at /candidates/cache/1/name: duplicate candidate '{cache}' (first defined at /candidates/cache/0)"
+                ),
+                "warning at /compliance_requirements/sbom/required_features/2: no ci_cd candidate provides 'attestation', so sbom can never be satisfied".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_lint_checks_default_services() {
+        let mut rules: Rules = serde_yaml::from_str(RULES).unwrap();
+        let mut scheduler = rules.default_services[1].clone();
+        scheduler.name = "scheduler".to_string();
+        scheduler.workload = Some(Workload::Cron);
+        let api = rules.default_services[0].clone();
+        rules.default_services.extend([scheduler, api]);
+
+        assert_eq!(
+            messages(&rules.validate()),
+            vec![
+                "error at /default_services/4/name: duplicate service 'api' (first defined at /default_services/0)",
+                "warning at /default_services/3/workload: no edge candidate runs cron workloads, so the scheduler service is never planned",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_lint_checks_store_penalty() {
+        let mut rules: Rules = serde_yaml::from_str(RULES).unwrap();
+        for extra_store in [-0.1, 1.5] {
+            rules.polyglot_penalties.extra_store = extra_store;
+            assert_eq!(
+                messages(&rules.validate()),
+                vec![format!(
+                    "error at /polyglot_penalties/extra_store: extra_store is {extra_store}; it must be between 0 and 1"
+                )]
+            );
+        }
+    }
+
+    #[test]
+    fn test_lint_attaches_lines_and_schema_errors() {
+        let source = r#"
+version: 1
+weights: { quality: 0.3, slo: 0.25, cost: 0.2, security: 0.15, ops: 0.1 }
+candidates:
+  language:
+    - name: Rust
+      metrics: { quality: 0.9, slo: 0.9, cost: 0.8, security: 0.9, ops: 0.8 }
+      regions: ["*"]
+  backend:
+    - name: Axum
+      requires:
+        language: Go
+      metrics: { quality: 0.9, slo: 0.9, cost: 0.8, security: 0.9, ops: 0.8 }
+      regions: ["*"]
+      montly_cost_base: 5
+  frontend: []
+  database: []
+  cache: []
+  queue: []
+  ai: []
+  infra: []
+  ci_cd:
+    - name: CI
+      metrics: { quality: 1.2, slo: 0.9, cost: 0.8, security: 0.9, ops: 0.8 }
+      regions: ["*"]
+"#;
+        let findings = lint_rules(source).unwrap();
+        let located: Vec<(Option<usize>, Severity, &str)> = findings
+            .iter()
+            .filter(|f| !f.message.starts_with("no "))
+            .map(|f| (f.line, f.severity, f.path.as_str()))
+            .collect();
+        assert_eq!(
+            located,
+            vec![
+                (Some(10), Severity::Warning, "/candidates/backend/0"),
+                (
+                    Some(12),
+                    Severity::Error,
+                    "/candidates/backend/0/requires/language"
+                ),
+                (
+                    Some(24),
+                    Severity::Error,
+                    "/candidates/ci_cd/0/metrics/quality"
+                ),
+            ]
+        );
+        assert!(findings[0].message.contains("montly_cost_base"));
+        let quality = findings.iter().find(|f| f.line == Some(24)).unwrap();
+        assert!(quality.message.contains("1.2"));
+    }
+
+    #[test]
+    fn test_lint_checks_categories() {
+        let source = r#"
+version: 1
+weights: { quality: 0.3, slo: 0.25, cost: 0.2, security: 0.15, ops: 0.1 }
+categories:
+  - name: language
+  - name: auth
+    depends_on: [lang]
+    count: 2
+  - name: auth
+  - name: search
+    cardinality: optional
+    needed: { when: [{}] }
+candidates:
+  language:
+    - name: Rust
+      metrics: { quality: 0.9, slo: 0.9, cost: 0.8, security: 0.9, ops: 0.8 }
+      regions: ["*"]
+  auth:
+    - name: Vault
+      requires: { database: PostgreSQL }
+      metrics: { quality: 0.9, slo: 0.9, cost: 0.8, security: 0.9, ops: 0.8 }
+      regions: ["*"]
+  queue: []
+"#;
+        let findings = lint_rules(source).unwrap();
+        assert_eq!(
+            messages(&findings),
+            vec![
+                "error at /categories/1/depends_on/0: unknown category 'lang'",
+                "warning at /categories/1/count: count only applies to categories with cardinality many",
+                "error at /categories/2/name: duplicate category 'auth' (first defined at /categories/1)",
+                "warning at /categories/3/needed/when/0: no conditions, so the category is always needed",
+                "error at /candidates/auth/0/requires/database: 'Vault' requires database 'PostgreSQL', but auth does not depend on database",
+                "warning at /candidates/queue: 'queue' is not a declared category, so its candidates are never selected",
+            ]
+        );
+        assert_eq!(findings[0].line, Some(7));
+
+        // Without the other mistakes the cycle is reported
+        let cyclic = source
+            .replace("[lang]", "[auth]")
+            .replace("    count: 2\n  - name: auth\n", "")
+            .replace("    needed: { when: [{}] }\n", "");
+        let findings = lint_rules(&cyclic).unwrap();
+        assert_eq!(
+            messages(&findings)[0],
+            "error at /categories: category dependencies form a cycle through auth"
+        );
+    }
+
+    #[test]
+    fn test_lint_checks_metric_references() {
+        let source = r#"
+version: 1
+weights: { quality: 0.3, slo: 0.2, cost: 0.2, security: 0.15, ops: 0.05 }
+dimensions:
+  - { name: developer_experience, weight: 0.1, default: 0.5 }
+  - { name: cost, weight: 0.0, default: 0.5 }
+  - { name: developer_experience, weight: 0.0, default: 0.5 }
+categories:
+  - name: language
+candidates:
+  language:
+    - name: Rust
+      metrics:
+        quality: 0.9
+        slo: 0.9
+        cost: 0.8
+        security: 0.9
+        ops: 0.8
+        developer_experience: 0.7
+        hiring_pool: 0.4
+      regions: ["*"]
+"#;
+        let findings = lint_rules(source).unwrap();
+        assert_eq!(
+            messages(&findings),
+            vec![
+                "error at /dimensions/1/name: 'cost' is a built-in metric; set its weight under weights",
+                "error at /dimensions/2/name: duplicate dimension 'developer_experience' (first defined at /dimensions/0)",
+                "warning at /candidates/language/0/metrics/hiring_pool: 'hiring_pool' is not a declared dimension, so it is not scored",
+            ]
+        );
+        assert_eq!(findings[2].line, Some(20));
+
+        let adjusted = source.replace(
+            "candidates:",
+            "adjustments:\n  - { when: {}, metric: carbon, weight: 0.1 }\ncandidates:",
+        );
+        let rules: Rules = serde_yaml::from_str(&adjusted).unwrap();
+        let found = messages(&rules.validate());
+        assert!(
+            found.contains(&"error at /adjustments/0/metric: unknown metric 'carbon'".to_string())
+        );
+        assert!(found.contains(&"warning at /adjustments/0/when: no conditions, so the adjustment always applies; change the weight instead".to_string()));
+
+        let lexicographic = source.replace(
+            "candidates:",
+            "scoring: { priority: [security, carbon] }\ncandidates:",
+        );
+        let rules: Rules = serde_yaml::from_str(&lexicographic).unwrap();
+        let found = messages(&rules.validate());
+        assert!(found.contains(&"warning at /scoring/priority: priority only applies to lexicographic scoring, not weighted_sum".to_string()));
+        assert!(
+            found.contains(&"error at /scoring/priority/1: unknown metric 'carbon'".to_string())
+        );
+
+        // Declared dimension weights count towards the sum and the range
+        let skewed = source.replace("weight: 0.1", "weight: -0.1");
+        let rules: Rules = serde_yaml::from_str(&skewed).unwrap();
+        let messages = messages(&rules.validate());
+        assert!(messages.contains(
+            &"error at /dimensions/0/weight: weight is -0.1; weights cannot be negative"
+                .to_string()
+        ));
+        assert!(messages
+            .iter()
+            .any(|m| m.starts_with("warning at /weights: weights sum to 0.8")));
+    }
+
+    #[test]
+    fn test_lint_rejects_unparseable_yaml() {
+        assert!(matches!(
+            lint_rules("weights: [").unwrap_err(),
+            RuneforgeError::Rules { .. }
+        ));
+        let findings = lint_rules("version: 1").unwrap();
+        assert!(findings.iter().any(|f| f.severity == Severity::Error
+            && f.message.contains("\"weights\" is a required property")));
+    }
+
+    #[test]
+    fn test_locate_line() {
+        let source = "a:\n  b:\n    - x: 1\n    - x: 2\n      y: [1, 2]\n  c: 3\n";
+        assert_eq!(locate_line(source, "/a/b/1/y"), Some(5));
+        assert_eq!(locate_line(source, "/a/b/1/y/0"), Some(5));
+        assert_eq!(locate_line(source, "/a/b/0/x"), Some(3));
+        assert_eq!(locate_line(source, "/a/c"), Some(6));
+        assert_eq!(locate_line(source, "/a/d"), Some(1));
+        assert_eq!(locate_line(source, ""), None);
+    }
+}
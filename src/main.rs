@@ -1,46 +1,205 @@
 #[cfg(not(feature = "std"))]
 compile_error!("The CLI binary requires the 'std' feature");
 
-use clap::{Parser, Subcommand};
+use clap::error::ErrorKind;
+use clap::{Args, CommandFactory, Parser, Subcommand, ValueEnum};
 #[cfg(feature = "std")]
-use runeforge::{observability, schema, selector::Selector};
+use runeforge::adapters::std_adapters::StdEnvAdapter;
+use runeforge::rules_source::RulesSource;
+use runeforge::schema::{SchemaKind, ValidationMode};
+use runeforge::scoring::ScoringStrategy;
+use runeforge::{layers, lint, observability, schema, selector::Selector, RuneforgeError};
 use std::fs;
+use std::path::Path;
 use std::process;
 use std::time::Instant;
 
 #[derive(Parser)]
-#[command(author, version, about, long_about = None)]
+#[command(author, about, long_about = None, disable_version_flag = true)]
 struct Cli {
+    /// Print version, and the rules source and hash plans would use
+    #[arg(short = 'V', long = "version")]
+    version: bool,
+
     #[command(subcommand)]
-    command: Commands,
+    command: Option<Commands>,
 }
 
 #[derive(Subcommand)]
 enum Commands {
     /// Generate an optimal technology stack plan from a blueprint
-    Plan {
-        /// Input blueprint file (YAML or JSON)
-        #[arg(short = 'f', long = "file", required = true)]
-        file: String,
+    Plan(PlanArgs),
+    /// Check rules files
+    #[command(subcommand)]
+    Rules(RulesCommand),
+    /// Generate or check the JSON Schemas
+    #[command(subcommand)]
+    Schema(SchemaCommand),
+}
 
-        /// Random seed for deterministic selection
-        #[arg(long = "seed", default_value = "42")]
-        seed: u64,
+#[derive(Subcommand, Debug, Clone)]
+enum RulesCommand {
+    /// Report schema violations and semantic problems in a rules file
+    Lint {
+        /// Rules file to lint; repeat to lint the merged layers
+        #[arg(long = "rules")]
+        rules: Vec<String>,
+
+        /// Fail on warnings as well as errors
+        #[arg(long = "strict")]
+        strict: bool,
+    },
+    /// Print the effective rules after merging every layer
+    Resolve {
+        /// Rules file; repeat to layer files, later ones merged onto earlier ones
+        #[arg(long = "rules")]
+        rules: Vec<String>,
 
         /// Output file (default: stdout)
         #[arg(long = "out")]
         out: Option<String>,
+    },
+}
 
-        /// Enable strict schema validation
-        #[arg(long = "strict")]
-        strict: bool,
+/// Rules from repeated `--rules` flags, `RUNEFORGE_RULES`, the XDG config
+/// directory or the embedded catalog, in that order.
+fn rules_source(flags: &[String]) -> RulesSource {
+    RulesSource::locate(flags, &StdEnvAdapter)
+}
 
-        /// Service combination search width
-        #[arg(long = "beam", default_value = "8")]
-        beam: usize,
+/// `--version` output: the version, then where the default rules come from.
+fn version_text() -> String {
+    let source = rules_source(&[]);
+    let hash = source
+        .hash()
+        .unwrap_or_else(|e| format!("unavailable: {e}"));
+    format!(
+        "runeforge {}\nrules: {source}\nrules hash: {hash}",
+        env!("CARGO_PKG_VERSION")
+    )
+}
+
+#[derive(Subcommand, Debug, Clone)]
+enum SchemaCommand {
+    /// Print the schema generated from the Rust types
+    Emit {
+        /// Document to emit the schema for
+        #[arg(value_enum)]
+        kind: SchemaArg,
+
+        /// Output file (default: stdout)
+        #[arg(long = "out")]
+        out: Option<String>,
+    },
+    /// Report differences between the generated schemas and the files on disk
+    Check {
+        /// Directory holding the checked-in schemas
+        #[arg(long = "dir", default_value = "schemas")]
+        dir: String,
     },
 }
 
+#[derive(ValueEnum, Debug, Clone, Copy)]
+enum SchemaArg {
+    Blueprint,
+    Stack,
+    Rules,
+}
+
+impl From<SchemaArg> for SchemaKind {
+    fn from(arg: SchemaArg) -> Self {
+        match arg {
+            SchemaArg::Blueprint => SchemaKind::Blueprint,
+            SchemaArg::Stack => SchemaKind::Stack,
+            SchemaArg::Rules => SchemaKind::Rules,
+        }
+    }
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy)]
+enum ScoringArg {
+    WeightedSum,
+    WeightedGeometric,
+    Lexicographic,
+    Topsis,
+}
+
+impl From<ScoringArg> for ScoringStrategy {
+    fn from(arg: ScoringArg) -> Self {
+        match arg {
+            ScoringArg::WeightedSum => ScoringStrategy::WeightedSum,
+            ScoringArg::WeightedGeometric => ScoringStrategy::WeightedGeometric,
+            ScoringArg::Lexicographic => ScoringStrategy::Lexicographic,
+            ScoringArg::Topsis => ScoringStrategy::Topsis,
+        }
+    }
+}
+
+#[derive(Args, Debug, Clone)]
+struct PlanArgs {
+    /// Input blueprint file (YAML or JSON)
+    #[arg(short = 'f', long = "file", required = true)]
+    file: String,
+
+    /// Random seed for deterministic selection
+    #[arg(long = "seed", default_value = "42")]
+    seed: u64,
+
+    /// Output file (default: stdout)
+    #[arg(long = "out")]
+    out: Option<String>,
+
+    /// Rules file; repeat to layer files, later ones merged onto earlier ones
+    #[arg(long = "rules")]
+    rules: Vec<String>,
+
+    /// Treat unknown fields, schema drift and warnings as errors
+    #[arg(long = "strict")]
+    strict: bool,
+
+    /// Service combination search width
+    #[arg(long = "beam", default_value = "8")]
+    beam: usize,
+
+    /// Output every non-dominated plan (score, cost, security, SLO, ops) as a JSON array
+    #[arg(long = "pareto")]
+    pareto: bool,
+
+    /// Most plans `--pareto` outputs, spread along the frontier; 0 for all
+    #[arg(long = "pareto-limit", default_value = "0")]
+    pareto_limit: usize,
+
+    /// Include a per-metric score breakdown in every decision
+    #[arg(long = "explain")]
+    explain: bool,
+
+    /// Scoring strategy, overriding `scoring.strategy` in the rules
+    #[arg(long = "scoring", value_enum)]
+    scoring: Option<ScoringArg>,
+
+    /// When no stack fits, output the smallest constraint relaxations that would
+    #[arg(long = "diagnose")]
+    diagnose: bool,
+}
+
+impl Default for PlanArgs {
+    fn default() -> Self {
+        PlanArgs {
+            file: String::new(),
+            seed: 42,
+            out: None,
+            rules: Vec::new(),
+            strict: false,
+            beam: 8,
+            pareto: false,
+            pareto_limit: 0,
+            explain: false,
+            scoring: None,
+            diagnose: false,
+        }
+    }
+}
+
 fn main() {
     // Initialize observability
     if let Err(e) = observability::init_observability() {
@@ -49,56 +208,39 @@ fn main() {
 
     let cli = Cli::parse();
 
-    match &cli.command {
-        Commands::Plan {
-            file,
-            seed,
-            out,
-            strict,
-            beam,
-        } => {
-            if let Err(e) = run_plan(file, *seed, out.as_deref(), *strict, *beam) {
-                eprintln!("Error: {e}");
-                // Determine exit code based on error type
-                let exit_code = if e.contains("Failed to parse blueprint") || e.contains("schema") {
-                    1 // Input schema error
-                } else if e.contains("output schema") {
-                    2 // Output schema error
-                } else if e.contains("No suitable") || e.contains("No stack found") {
-                    3 // No matching stack found
-                } else {
-                    1 // Default to input error
-                };
-                process::exit(exit_code);
-            }
-        }
+    if cli.version {
+        println!("{}", version_text());
+        return;
+    }
+    let result = match &cli.command {
+        Some(Commands::Plan(args)) => run_plan(args),
+        Some(Commands::Rules(command)) => run_rules(command),
+        Some(Commands::Schema(command)) => run_schema(command),
+        None => Cli::command()
+            .error(ErrorKind::MissingSubcommand, "a subcommand is required")
+            .exit(),
+    };
+    if let Err(e) = result {
+        eprintln!("Error: {e}");
+        process::exit(e.exit_code());
     }
 }
 
-fn run_plan(
-    file: &str,
-    seed: u64,
-    out: Option<&str>,
-    _strict: bool,
-    beam: usize,
-) -> Result<(), String> {
-    run_plan_with_rules(file, seed, out, _strict, beam, "resources/rules.yaml")
+fn run_plan(args: &PlanArgs) -> Result<(), RuneforgeError> {
+    run_plan_with_rules(args, &rules_source(&args.rules))
 }
 
-fn run_plan_with_rules(
-    file: &str,
-    seed: u64,
-    out: Option<&str>,
-    _strict: bool,
-    beam: usize,
-    rules_path: &str,
-) -> Result<(), String> {
+fn run_plan_with_rules(args: &PlanArgs, rules: &RulesSource) -> Result<(), RuneforgeError> {
     let _start_time = Instant::now();
     let _span = observability::DurationSpan::new("run_plan");
+    let file = args.file.as_str();
 
     // Read input file
-    let input_content =
-        fs::read_to_string(file).map_err(|e| format!("Failed to read input file: {e}"))?;
+    let input_content = fs::read_to_string(file).map_err(|source| RuneforgeError::Io {
+        action: "read input file",
+        path: file.to_string(),
+        source,
+    })?;
 
     // Validate and parse blueprint
     let format = if file.ends_with(".json") {
@@ -108,68 +250,281 @@ fn run_plan_with_rules(
     };
     observability::log_blueprint_validation(input_content.len(), format);
 
-    let blueprint = match schema::validate_blueprint(&input_content) {
-        Ok(bp) => bp,
+    let mode = if args.strict {
+        ValidationMode::Strict
+    } else {
+        ValidationMode::Lenient
+    };
+    let blueprint = match schema::validate_blueprint_with(&input_content, mode) {
+        Ok((bp, warnings)) => {
+            for warning in warnings {
+                eprintln!("Warning: {warning}");
+            }
+            bp
+        }
         Err(e) => {
-            observability::log_error("blueprint_validation", &e);
-            return Err(format!("Failed to parse blueprint: {e}"));
+            observability::log_error("blueprint_validation", &e.to_string());
+            return Err(e);
         }
     };
 
-    // Load rules
-    let rules_content =
-        fs::read_to_string(rules_path).map_err(|e| format!("Failed to read rules file: {e}"))?;
+    // Load and merge the rules layers
+    let rules = layers::into_rules(rules.load()?)?;
 
     // Create selector and generate plan
-    observability::log_selection_start(&blueprint.project_name, seed);
-    let selector = Selector::new(&rules_content, seed, beam)?;
-    let plan = match selector.select(&blueprint) {
+    observability::log_selection_start(&blueprint.project_name, args.seed);
+    let mut selector =
+        Selector::from_rules(rules, args.seed, args.beam)?.with_explain(args.explain);
+    if let Some(scoring) = args.scoring {
+        selector = selector.with_scoring(scoring.into());
+    }
+    for warning in selector.warnings(&blueprint) {
+        if args.strict {
+            return Err(RuneforgeError::BlueprintSchema {
+                path: String::new(),
+                message: format!("{warning} (warnings are errors with --strict)"),
+            });
+        }
+        eprintln!("Warning: {warning}");
+    }
+    let plans = if args.pareto {
+        selector.select_pareto(
+            &blueprint,
+            (args.pareto_limit > 0).then_some(args.pareto_limit),
+        )
+    } else {
+        selector.select(&blueprint).map(|plan| vec![plan])
+    };
+    let plans = match plans {
         Ok(p) => p,
         Err(e) => {
-            observability::log_error("selection", &e);
+            observability::log_error("selection", &e.to_string());
+            if args.diagnose && e.is_infeasible() {
+                write_diagnosis(args, &selector, &blueprint, &e)?;
+            }
             return Err(e);
         }
     };
 
     // Validate output
-    if let Err(e) = schema::validate_stack_plan(&plan) {
-        return Err(format!("Output schema validation failed: {e}"));
+    for plan in &plans {
+        for warning in schema::validate_stack_plan_with(plan, mode)? {
+            eprintln!("Warning: {warning}");
+        }
     }
 
-    // Serialize to JSON
-    let output_json = serde_json::to_string_pretty(&plan)
-        .map_err(|e| format!("Failed to serialize output: {e}"))?;
-
-    // Write output
-    if let Some(output_file) = out {
-        fs::write(output_file, &output_json)
-            .map_err(|e| format!("Failed to write output file: {e}"))?;
+    // Serialize to JSON; Pareto mode always emits an array
+    let output_json = if args.pareto {
+        serde_json::to_string_pretty(&plans)
     } else {
-        println!("{output_json}");
+        serde_json::to_string_pretty(&plans[0])
     }
+    .map_err(serialize_error)?;
+    write_output(args, &output_json)?;
 
     // Log final selection summary
-    let stack_summary = vec![
-        ("language".to_string(), plan.stack.language.clone()),
-        ("frontend".to_string(), plan.stack.frontend.clone()),
-        ("backend".to_string(), plan.stack.backend.clone()),
-        ("database".to_string(), plan.stack.database.clone()),
-        ("cache".to_string(), plan.stack.cache.clone()),
-        ("queue".to_string(), plan.stack.queue.clone()),
-        ("ai".to_string(), plan.stack.ai.join(", ")),
-        ("infra".to_string(), plan.stack.infra.clone()),
-        ("ci_cd".to_string(), plan.stack.ci_cd.clone()),
-    ];
-    observability::log_final_selection(&stack_summary, plan.estimated.monthly_cost_usd);
+    for plan in &plans {
+        let mut stack_summary = vec![("language".to_string(), plan.stack.language.clone())];
+        stack_summary.extend(
+            plan.stack
+                .components
+                .iter()
+                .map(|(category, entry)| (category.clone(), entry.to_string())),
+        );
+        observability::log_final_selection(&stack_summary, plan.estimated.monthly_cost_usd);
+    }
+
+    Ok(())
+}
+
+fn run_rules(command: &RulesCommand) -> Result<(), RuneforgeError> {
+    match command {
+        RulesCommand::Lint { rules, strict } => lint_rules_source(&rules_source(rules), *strict),
+        RulesCommand::Resolve { rules, out } => {
+            let merged = rules_source(rules).load()?;
+            let yaml = serde_yaml::to_string(&merged).map_err(|e| RuneforgeError::Rules {
+                message: e.to_string(),
+            })?;
+            match out {
+                Some(path) => fs::write(path, yaml).map_err(|source| RuneforgeError::Io {
+                    action: "write output file",
+                    path: path.clone(),
+                    source,
+                }),
+                None => {
+                    print!("{yaml}");
+                    Ok(())
+                }
+            }
+        }
+    }
+}
+
+/// Print every finding as `file:line: severity at path: message` and fail
+/// on errors, or on warnings with `--strict`.
+///
+/// Layered rules are linted after merging; their findings carry no line
+/// numbers because the merged document exists in no file.
+fn lint_rules_source(source: &RulesSource, strict: bool) -> Result<(), RuneforgeError> {
+    let findings = match source.single_document()? {
+        Some(document) => lint::lint_rules(&document)?,
+        None => {
+            let yaml =
+                serde_yaml::to_string(&source.load()?).map_err(|e| RuneforgeError::Rules {
+                    message: e.to_string(),
+                })?;
+            let mut findings = lint::lint_rules(&yaml)?;
+            for finding in &mut findings {
+                finding.line = None;
+            }
+            findings
+        }
+    };
+    let path = source.to_string();
+    let path = path.as_str();
 
+    for finding in &findings {
+        match finding.line {
+            Some(line) => eprintln!("{path}:{line}: {finding}"),
+            None => eprintln!("{path}: {finding}"),
+        }
+    }
+    let errors = findings
+        .iter()
+        .filter(|f| f.severity == lint::Severity::Error)
+        .count();
+    let warnings = findings.len() - errors;
+    if errors > 0 || (strict && warnings > 0) {
+        return Err(RuneforgeError::RulesLint {
+            file: path.to_string(),
+            errors,
+            warnings,
+        });
+    }
+    eprintln!("{path}: {errors} error(s), {warnings} warning(s)");
     Ok(())
 }
 
+fn run_schema(command: &SchemaCommand) -> Result<(), RuneforgeError> {
+    match command {
+        SchemaCommand::Emit { kind, out } => {
+            let json = schema_json(SchemaKind::from(*kind))?;
+            match out {
+                Some(path) => fs::write(path, json).map_err(|source| RuneforgeError::Io {
+                    action: "write schema file",
+                    path: path.clone(),
+                    source,
+                }),
+                None => {
+                    print!("{json}");
+                    Ok(())
+                }
+            }
+        }
+        SchemaCommand::Check { dir } => check_schemas(dir),
+    }
+}
+
+/// Pretty-printed schema with a trailing newline, as checked in under `schemas/`.
+fn schema_json(kind: SchemaKind) -> Result<String, RuneforgeError> {
+    let json = serde_json::to_string_pretty(&kind.generate()).map_err(serialize_error)?;
+    Ok(format!("{json}\n"))
+}
+
+/// Compare every generated schema with its file in `dir`, reporting all
+/// differences and failing with the first file that has drifted.
+fn check_schemas(dir: &str) -> Result<(), RuneforgeError> {
+    let mut drift = None;
+    for kind in SchemaKind::ALL {
+        let path = Path::new(dir).join(kind.file_name());
+        let file = path.display().to_string();
+        let content = fs::read_to_string(&path).map_err(|source| RuneforgeError::Io {
+            action: "read schema file",
+            path: file.clone(),
+            source,
+        })?;
+        let on_disk: serde_json::Value =
+            serde_json::from_str(&content).map_err(|e| RuneforgeError::SchemaDrift {
+                file: file.clone(),
+                differences: vec![format!("not valid JSON: {e}")],
+            })?;
+
+        let differences = schema::schema_differences(&kind.generate(), &on_disk);
+        if !differences.is_empty() {
+            for difference in &differences {
+                eprintln!("{file}: {difference}");
+            }
+            drift.get_or_insert(RuneforgeError::SchemaDrift { file, differences });
+        } else {
+            eprintln!("{file}: up to date");
+        }
+    }
+    drift.map_or(Ok(()), Err)
+}
+
+fn serialize_error(e: serde_json::Error) -> RuneforgeError {
+    RuneforgeError::OutputSchema {
+        path: String::new(),
+        message: format!("Failed to serialize output: {e}"),
+    }
+}
+
+fn write_output(args: &PlanArgs, json: &str) -> Result<(), RuneforgeError> {
+    if let Some(output_file) = &args.out {
+        fs::write(output_file, json).map_err(|source| RuneforgeError::Io {
+            action: "write output file",
+            path: output_file.clone(),
+            source,
+        })
+    } else {
+        println!("{json}");
+        Ok(())
+    }
+}
+
+/// Write the relaxations that would make an infeasible blueprint feasible.
+fn write_diagnosis(
+    args: &PlanArgs,
+    selector: &Selector,
+    blueprint: &schema::Blueprint,
+    error: &RuneforgeError,
+) -> Result<(), RuneforgeError> {
+    let relaxations = selector.diagnose(blueprint);
+    if relaxations.is_empty() {
+        eprintln!("No relaxation of up to two constraints makes this blueprint feasible");
+    }
+    for relaxation in &relaxations {
+        eprintln!(
+            "Suggestion: {} (monthly cost ${})",
+            relaxation.changes.join("; "),
+            relaxation.plan.estimated.monthly_cost_usd
+        );
+    }
+
+    let diagnosis = schema::Diagnosis {
+        error: error.to_string(),
+        relaxations,
+    };
+    let json = serde_json::to_string_pretty(&diagnosis).map_err(serialize_error)?;
+    write_output(args, &json)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use tempfile::TempDir;
 
+    fn rules_file(path: &str) -> RulesSource {
+        RulesSource::Flags(vec![path.into()])
+    }
+
+    fn plan_args(file: &str) -> PlanArgs {
+        PlanArgs {
+            file: file.to_string(),
+            ..PlanArgs::default()
+        }
+    }
+
     fn create_test_blueprint(content: &str) -> (TempDir, String) {
         let dir = TempDir::new().unwrap();
         let file_path = dir.path().join("test_blueprint.yaml");
@@ -261,12 +616,11 @@ traffic_profile:
         let output_path = output_dir.path().join("output.json");
 
         let result = run_plan_with_rules(
-            &bp_path,
-            42,
-            Some(output_path.to_str().unwrap()),
-            false,
-            8,
-            &rules_path,
+            &PlanArgs {
+                out: Some(output_path.to_str().unwrap().to_string()),
+                ..plan_args(&bp_path)
+            },
+            &rules_file(&rules_path),
         );
 
         assert!(result.is_ok());
@@ -281,6 +635,200 @@ traffic_profile:
         assert!(parsed.get("meta").is_some());
     }
 
+    #[test]
+    fn test_run_plan_strict_fails_on_unknown_prefs() {
+        let blueprint_content = r#"
+project_name: "test-project"
+goals:
+  - "Build a web app"
+constraints:
+  monthly_cost_usd_max: 1000
+traffic_profile:
+  rps_peak: 1000
+  global: true
+  latency_sensitive: false
+prefs:
+  databse: ["PostgreSQL"]
+"#;
+
+        let (_bp_dir, bp_path) = create_test_blueprint(blueprint_content);
+        let (_rules_dir, rules_path) = create_test_rules();
+        let output_dir = TempDir::new().unwrap();
+        let output_path = output_dir.path().join("output.json");
+        let args = PlanArgs {
+            out: Some(output_path.to_str().unwrap().to_string()),
+            ..plan_args(&bp_path)
+        };
+
+        // Without --strict the misspelled category is only a warning
+        run_plan_with_rules(&args, &rules_file(&rules_path)).unwrap();
+
+        let err = run_plan_with_rules(
+            &PlanArgs {
+                strict: true,
+                ..args
+            },
+            &rules_file(&rules_path),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid blueprint: Unknown category 'databse' in prefs; it is ignored (warnings are errors with --strict)"
+        );
+    }
+
+    #[test]
+    fn test_run_plan_merges_rules_layers() {
+        let blueprint_content = r#"
+project_name: "test-project"
+goals:
+  - "Build a web app"
+constraints:
+  monthly_cost_usd_max: 1000
+traffic_profile:
+  rps_peak: 1000
+  global: true
+  latency_sensitive: false
+"#;
+        let overlay = r#"
+candidates:
+  database:
+    - { name: PlanetScale, remove: true }
+    - { name: PostgreSQL, remove: true }
+    - { name: MongoDB, remove: true }
+    - { name: Redis, remove: true }
+"#;
+
+        let (_bp_dir, bp_path) = create_test_blueprint(blueprint_content);
+        let overlay_dir = TempDir::new().unwrap();
+        let overlay_path = overlay_dir.path().join("team.yaml");
+        fs::write(&overlay_path, overlay).unwrap();
+        let output_path = overlay_dir.path().join("output.json");
+
+        let args = PlanArgs {
+            out: Some(output_path.to_str().unwrap().to_string()),
+            rules: vec![
+                "resources/rules.yaml".to_string(),
+                overlay_path.to_str().unwrap().to_string(),
+            ],
+            ..plan_args(&bp_path)
+        };
+        run_plan(&args).unwrap();
+
+        let output_content = fs::read_to_string(&output_path).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&output_content).unwrap();
+        assert_eq!(parsed["stack"]["database"], "DynamoDB");
+    }
+
+    #[test]
+    fn test_run_plan_pareto_outputs_array() {
+        let blueprint_content = r#"
+project_name: "test-project"
+goals:
+  - "Build a web app"
+constraints:
+  monthly_cost_usd_max: 1000
+traffic_profile:
+  rps_peak: 1000
+  global: true
+  latency_sensitive: false
+"#;
+
+        let (_bp_dir, bp_path) = create_test_blueprint(blueprint_content);
+        let (_rules_dir, rules_path) = create_test_rules();
+        let output_dir = TempDir::new().unwrap();
+        let output_path = output_dir.path().join("pareto.json");
+
+        let result = run_plan_with_rules(
+            &PlanArgs {
+                out: Some(output_path.to_str().unwrap().to_string()),
+                pareto: true,
+                ..plan_args(&bp_path)
+            },
+            &rules_file(&rules_path),
+        );
+
+        assert!(result.is_ok());
+        let output_content = fs::read_to_string(&output_path).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&output_content).unwrap();
+        let plans = parsed.as_array().expect("Pareto output should be an array");
+        assert!(!plans.is_empty());
+        assert!(plans.iter().all(|p| p.get("stack").is_some()));
+
+        let result = run_plan_with_rules(
+            &PlanArgs {
+                out: Some(output_path.to_str().unwrap().to_string()),
+                pareto: true,
+                pareto_limit: 1,
+                ..plan_args(&bp_path)
+            },
+            &rules_file(&rules_path),
+        );
+        assert!(result.is_ok());
+        let parsed: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&output_path).unwrap()).unwrap();
+        assert_eq!(parsed.as_array().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_run_plan_explain_adds_breakdowns() {
+        let blueprint_content = r#"
+project_name: "test-project"
+goals:
+  - "Build a web app"
+constraints:
+  monthly_cost_usd_max: 1000
+traffic_profile:
+  rps_peak: 1000
+  global: true
+  latency_sensitive: true
+"#;
+
+        let (_bp_dir, bp_path) = create_test_blueprint(blueprint_content);
+        let (_rules_dir, rules_path) = create_test_rules();
+        let output_dir = TempDir::new().unwrap();
+        let plain_path = output_dir.path().join("plain.json");
+        let explained_path = output_dir.path().join("explained.json");
+
+        let plain = PlanArgs {
+            out: Some(plain_path.to_str().unwrap().to_string()),
+            ..plan_args(&bp_path)
+        };
+        let explained = PlanArgs {
+            out: Some(explained_path.to_str().unwrap().to_string()),
+            explain: true,
+            ..plan_args(&bp_path)
+        };
+        let geometric = PlanArgs {
+            scoring: Some(ScoringArg::WeightedGeometric),
+            ..explained.clone()
+        };
+        assert!(run_plan_with_rules(&plain, &rules_file(&rules_path)).is_ok());
+        assert!(run_plan_with_rules(&geometric, &rules_file(&rules_path)).is_ok());
+        let geometric: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&explained_path).unwrap()).unwrap();
+        assert_eq!(
+            geometric["decisions"][0]["breakdown"]["strategy"],
+            "weighted_geometric"
+        );
+        assert!(run_plan_with_rules(&explained, &rules_file(&rules_path)).is_ok());
+
+        let plain: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&plain_path).unwrap()).unwrap();
+        let explained: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&explained_path).unwrap()).unwrap();
+
+        for decision in plain["decisions"].as_array().unwrap() {
+            assert!(decision.get("breakdown").is_none());
+        }
+        for decision in explained["decisions"].as_array().unwrap() {
+            let breakdown = &decision["breakdown"];
+            assert_eq!(breakdown["metrics"].as_array().unwrap().len(), 5);
+            assert_eq!(breakdown["adjustments"].as_array().unwrap().len(), 2);
+            assert!((breakdown["normalization"].as_f64().unwrap() - 1.15).abs() < 1e-9);
+        }
+    }
+
     #[test]
     fn test_run_plan_invalid_blueprint() {
         let blueprint_content = r#"
@@ -296,20 +844,92 @@ traffic_profile:
         let (_bp_dir, bp_path) = create_test_blueprint(blueprint_content);
         let (_rules_dir, rules_path) = create_test_rules();
 
-        let result = run_plan_with_rules(&bp_path, 42, None, false, 8, &rules_path);
+        let err = run_plan_with_rules(&plan_args(&bp_path), &rules_file(&rules_path)).unwrap_err();
 
-        assert!(result.is_err());
-        assert!(result.unwrap_err().contains("Failed to parse blueprint"));
+        assert!(matches!(
+            &err,
+            RuneforgeError::BlueprintSchema { path, .. } if path == "/project_name"
+        ));
+        assert_eq!(err.exit_code(), 1);
+    }
+
+    #[test]
+    fn test_run_plan_strict_rejects_unknown_fields() {
+        let blueprint_content = r#"
+project_name: "test-project"
+goals:
+  - "Build a web app"
+constraints:
+  monthly_cost_usd_max: 1000
+traffic_profile:
+  rps_peak: 1000
+  global: true
+  latency_sensitive: false
+owner: "platform-team"
+"#;
+
+        let (_bp_dir, bp_path) = create_test_blueprint(blueprint_content);
+        let (_rules_dir, rules_path) = create_test_rules();
+        let output_dir = TempDir::new().unwrap();
+        let output_path = output_dir.path().join("output.json");
+
+        // Unknown fields are only warnings by default
+        let lenient = PlanArgs {
+            out: Some(output_path.to_str().unwrap().to_string()),
+            ..plan_args(&bp_path)
+        };
+        assert!(run_plan_with_rules(&lenient, &rules_file(&rules_path)).is_ok());
+
+        let strict = PlanArgs {
+            strict: true,
+            ..lenient
+        };
+        let err = run_plan_with_rules(&strict, &rules_file(&rules_path)).unwrap_err();
+        assert!(matches!(err, RuneforgeError::BlueprintSchema { .. }));
+        assert_eq!(err.exit_code(), 1);
+        assert!(err.to_string().contains("'owner' was unexpected"));
+    }
+
+    #[test]
+    fn test_rules_lint_fails_on_errors_and_strict_warnings() {
+        assert!(lint_rules_source(&RulesSource::Embedded, true).is_ok());
+
+        let rules = fs::read_to_string("resources/rules.yaml").unwrap();
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("rules.yaml");
+        let path = path.to_str().unwrap();
+
+        // Weights summing to 1.1 only warn
+        fs::write(path, rules.replacen("ops: 0.10", "ops: 0.20", 1)).unwrap();
+        assert!(lint_rules_source(&rules_file(path), false).is_ok());
+        let err = lint_rules_source(&rules_file(path), true).unwrap_err();
+        assert!(matches!(
+            err,
+            RuneforgeError::RulesLint {
+                errors: 0,
+                warnings: 1,
+                ..
+            }
+        ));
+
+        fs::write(path, rules.replacen("quality: 0.9,", "quality: 9,", 1)).unwrap();
+        let err = lint_rules_source(&rules_file(path), false).unwrap_err();
+        assert!(matches!(err, RuneforgeError::RulesLint { errors: 1, .. }));
+        assert_eq!(err.exit_code(), 1);
     }
 
     #[test]
     fn test_run_plan_file_not_found() {
         let (_rules_dir, rules_path) = create_test_rules();
 
-        let result = run_plan_with_rules("/nonexistent/file.yaml", 42, None, false, 8, &rules_path);
+        let result = run_plan_with_rules(
+            &plan_args("/nonexistent/file.yaml"),
+            &rules_file(&rules_path),
+        );
 
-        assert!(result.is_err());
-        assert!(result.unwrap_err().contains("Failed to read input file"));
+        let err = result.unwrap_err();
+        assert!(err.to_string().contains("Failed to read input file"));
+        assert!(matches!(err, RuneforgeError::Io { .. }));
     }
 
     #[test]
@@ -327,10 +947,12 @@ traffic_profile:
 
         let (_bp_dir, bp_path) = create_test_blueprint(blueprint_content);
 
-        let result = run_plan_with_rules(&bp_path, 42, None, false, 8, "/nonexistent/rules.yaml");
+        let result =
+            run_plan_with_rules(&plan_args(&bp_path), &rules_file("/nonexistent/rules.yaml"));
 
-        assert!(result.is_err());
-        assert!(result.unwrap_err().contains("Failed to read rules file"));
+        let err = result.unwrap_err();
+        assert!(err.to_string().contains("Failed to read rules file"));
+        assert_eq!(err.exit_code(), 1);
     }
 
     #[test]
@@ -355,20 +977,18 @@ traffic_profile:
 
         // Run twice with same seed
         let result1 = run_plan_with_rules(
-            &bp_path,
-            42,
-            Some(output_path1.to_str().unwrap()),
-            false,
-            8,
-            &rules_path,
+            &PlanArgs {
+                out: Some(output_path1.to_str().unwrap().to_string()),
+                ..plan_args(&bp_path)
+            },
+            &rules_file(&rules_path),
         );
         let result2 = run_plan_with_rules(
-            &bp_path,
-            42,
-            Some(output_path2.to_str().unwrap()),
-            false,
-            8,
-            &rules_path,
+            &PlanArgs {
+                out: Some(output_path2.to_str().unwrap().to_string()),
+                ..plan_args(&bp_path)
+            },
+            &rules_file(&rules_path),
         );
 
         assert!(result1.is_ok());
@@ -402,16 +1022,66 @@ traffic_profile:
         let (_bp_dir, bp_path) = create_test_blueprint(blueprint_content);
         let (_rules_dir, rules_path) = create_test_rules();
 
-        let result = run_plan_with_rules(&bp_path, 42, None, false, 8, &rules_path);
+        let result = run_plan_with_rules(&plan_args(&bp_path), &rules_file(&rules_path));
 
         assert!(result.is_err());
         let err = result.unwrap_err();
+        assert_eq!(err.exit_code(), 3);
+        let err = err.to_string();
         assert!(
             err.contains("cost constraint") || err.contains("No suitable"),
             "Expected cost constraint error, got: {err}"
         );
     }
 
+    #[test]
+    fn test_run_plan_diagnose_suggests_relaxations() {
+        let blueprint_content = r#"
+project_name: "test-project"
+goals:
+  - "Build a web app"
+constraints:
+  monthly_cost_usd_max: 10
+traffic_profile:
+  rps_peak: 1000
+  global: true
+  latency_sensitive: false
+"#;
+
+        let (_bp_dir, bp_path) = create_test_blueprint(blueprint_content);
+        let (_rules_dir, rules_path) = create_test_rules();
+        let output_dir = TempDir::new().unwrap();
+        let output_path = output_dir.path().join("diagnosis.json");
+
+        let result = run_plan_with_rules(
+            &PlanArgs {
+                out: Some(output_path.to_str().unwrap().to_string()),
+                diagnose: true,
+                ..plan_args(&bp_path)
+            },
+            &rules_file(&rules_path),
+        );
+
+        // Still an infeasible blueprint, so the exit code stays 3
+        let err = result.unwrap_err();
+        assert!(err.is_infeasible());
+        let err = err.to_string();
+
+        let diagnosis: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&output_path).unwrap()).unwrap();
+        assert_eq!(diagnosis["error"], err.as_str());
+        let relaxations = diagnosis["relaxations"].as_array().unwrap();
+        assert_eq!(relaxations.len(), 1);
+        assert_eq!(
+            relaxations[0]["changes"][0],
+            "Raise monthly_cost_usd_max from $10 to $620"
+        );
+        assert_eq!(
+            relaxations[0]["plan"]["estimated"]["monthly_cost_usd"],
+            620.0
+        );
+    }
+
     #[test]
     fn test_run_plan_json_input() {
         let blueprint_content = r#"{
@@ -433,8 +1103,10 @@ traffic_profile:
 
         let (_rules_dir, rules_path) = create_test_rules();
 
-        let result =
-            run_plan_with_rules(file_path.to_str().unwrap(), 42, None, false, 8, &rules_path);
+        let result = run_plan_with_rules(
+            &plan_args(file_path.to_str().unwrap()),
+            &rules_file(&rules_path),
+        );
 
         assert!(result.is_ok());
     }
@@ -458,7 +1130,7 @@ traffic_profile:
         let (_bp_dir, bp_path) = create_test_blueprint(blueprint_content);
         let (_rules_dir, rules_path) = create_test_rules();
 
-        let result = run_plan_with_rules(&bp_path, 42, None, false, 8, &rules_path);
+        let result = run_plan_with_rules(&plan_args(&bp_path), &rules_file(&rules_path));
 
         assert!(result.is_ok());
     }
@@ -475,9 +1147,10 @@ goals:
         let (_bp_dir, bp_path) = create_test_blueprint(blueprint_content);
         let (_rules_dir, rules_path) = create_test_rules();
 
-        let result = run_plan_with_rules(&bp_path, 42, None, false, 8, &rules_path);
+        let result = run_plan_with_rules(&plan_args(&bp_path), &rules_file(&rules_path));
 
-        assert!(result.is_err());
-        assert!(result.unwrap_err().contains("Failed to parse blueprint"));
+        let err = result.unwrap_err();
+        assert!(err.to_string().contains("Failed to parse blueprint"));
+        assert!(matches!(err, RuneforgeError::BlueprintParse { .. }));
     }
 }
@@ -133,6 +133,18 @@ pub fn log_constraint_evaluation(
     }
 }
 
+/// Log a candidate removed by a constraint filter
+#[cfg(feature = "std")]
+#[instrument]
+pub fn log_candidate_rejected(component: &str, candidate: &str, reason: &str) {
+    debug!(
+        component = component,
+        candidate = candidate,
+        reason = reason,
+        "Candidate rejected"
+    );
+}
+
 /// Log scoring details
 #[cfg(feature = "std")]
 #[instrument]
@@ -245,6 +257,9 @@ pub fn log_constraint_evaluation(
 ) {
 }
 
+#[cfg(not(feature = "std"))]
+pub fn log_candidate_rejected(_component: &str, _candidate: &str, _reason: &str) {}
+
 #[cfg(not(feature = "std"))]
 pub fn log_scoring(
     _component: &str,
@@ -0,0 +1,314 @@
+//! Traffic-scaled pricing for candidates.
+//!
+//! Candidates may declare a `pricing` curve on top of their flat
+//! `monthly_cost_base`: per-request prices (optionally tiered by monthly
+//! volume), per-instance throughput and egress. The curve is evaluated
+//! against a [`TrafficEstimate`] derived from the blueprint's traffic profile.
+
+use crate::schema::TrafficProfile;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Average load as a fraction of `rps_peak`.
+pub const AVERAGE_LOAD_FACTOR: f64 = 0.2;
+/// Seconds in a 30-day month.
+pub const SECONDS_PER_MONTH: f64 = 2_592_000.0;
+/// Response payload assumed when the blueprint does not set one.
+pub const DEFAULT_PAYLOAD_KB: f64 = 2.0;
+/// Share of reads assumed when the blueprint does not set one.
+pub const DEFAULT_READ_RATIO: f64 = 0.8;
+
+/// Monthly traffic implied by a blueprint's traffic profile.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrafficEstimate {
+    pub rps_peak: f64,
+    pub monthly_requests: f64,
+    pub read_ratio: f64,
+    /// Response bytes served to clients, in GB
+    pub egress_gb: f64,
+}
+
+impl TrafficEstimate {
+    pub fn from_profile(profile: &TrafficProfile) -> Self {
+        let read_ratio = profile.read_ratio.unwrap_or(DEFAULT_READ_RATIO);
+        let payload_kb = profile.avg_payload_kb.unwrap_or(DEFAULT_PAYLOAD_KB);
+        let monthly_requests = profile.rps_peak * AVERAGE_LOAD_FACTOR * SECONDS_PER_MONTH;
+
+        TrafficEstimate {
+            rps_peak: profile.rps_peak,
+            monthly_requests,
+            read_ratio,
+            egress_gb: monthly_requests * read_ratio * payload_kb / 1_000_000.0,
+        }
+    }
+
+    /// Fraction of requests that reach a component serving `share`.
+    fn fraction(&self, share: TrafficShare) -> f64 {
+        match share {
+            TrafficShare::All => 1.0,
+            TrafficShare::Reads => self.read_ratio,
+            TrafficShare::Writes => 1.0 - self.read_ratio,
+        }
+    }
+}
+
+/// Part of the traffic a component handles.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum TrafficShare {
+    #[default]
+    All,
+    Reads,
+    Writes,
+}
+
+/// Per-request price for monthly volume up to `up_to_million` requests.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PriceTier {
+    /// Cumulative upper bound in millions of requests; open-ended when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(range(min = 0))]
+    pub up_to_million: Option<f64>,
+    #[schemars(range(min = 0))]
+    pub per_million: f64,
+}
+
+/// Traffic-dependent costs added to a candidate's `monthly_cost_base`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct Pricing {
+    #[serde(default)]
+    pub serves: TrafficShare,
+    /// Flat USD per million requests, used when `tiers` is empty
+    #[serde(default)]
+    #[schemars(range(min = 0))]
+    pub per_million_requests: f64,
+    /// Volume tiers in ascending order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tiers: Vec<PriceTier>,
+    /// Peak requests per second one instance handles
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(range(min = 0))]
+    pub instance_rps: Option<f64>,
+    /// USD per instance per month
+    #[serde(default)]
+    #[schemars(range(min = 0))]
+    pub instance_monthly: f64,
+    /// USD per GB of egress
+    #[serde(default)]
+    #[schemars(range(min = 0))]
+    pub egress_per_gb: f64,
+}
+
+impl Pricing {
+    /// Monthly cost in USD for the given traffic.
+    pub fn monthly_cost(&self, traffic: &TrafficEstimate) -> f64 {
+        let fraction = traffic.fraction(self.serves);
+        let millions = traffic.monthly_requests * fraction / 1_000_000.0;
+
+        let requests = if self.tiers.is_empty() {
+            millions * self.per_million_requests
+        } else {
+            self.tiered_cost(millions)
+        };
+
+        let instances = match self.instance_rps {
+            Some(capacity) if capacity > 0.0 => {
+                (traffic.rps_peak * fraction / capacity).ceil().max(1.0)
+            }
+            _ => 0.0,
+        };
+
+        requests + instances * self.instance_monthly + traffic.egress_gb * self.egress_per_gb
+    }
+
+    /// The first number that is negative or not finite, or tier bound that
+    /// does not ascend, described for the rules error.
+    pub fn problem(&self) -> Option<String> {
+        let mut values = vec![
+            (
+                "per_million_requests".to_string(),
+                self.per_million_requests,
+            ),
+            ("instance_monthly".to_string(), self.instance_monthly),
+            ("egress_per_gb".to_string(), self.egress_per_gb),
+        ];
+        if let Some(capacity) = self.instance_rps {
+            values.push(("instance_rps".to_string(), capacity));
+        }
+        for (index, tier) in self.tiers.iter().enumerate() {
+            values.push((format!("tiers[{index}].per_million"), tier.per_million));
+            if let Some(up_to) = tier.up_to_million {
+                values.push((format!("tiers[{index}].up_to_million"), up_to));
+            }
+        }
+        if let Some((field, value)) = values
+            .into_iter()
+            .find(|(_, value)| !value.is_finite() || *value < 0.0)
+        {
+            return Some(format!(
+                "pricing.{field} must be a non-negative number, not {value}"
+            ));
+        }
+
+        let mut floor = 0.0;
+        for (index, tier) in self.tiers.iter().enumerate() {
+            match tier.up_to_million {
+                Some(up_to) if up_to <= floor && index > 0 => {
+                    return Some(format!(
+                        "pricing.tiers[{index}].up_to_million must be above the previous tier's {floor}"
+                    ));
+                }
+                Some(up_to) => floor = up_to,
+                None if index + 1 < self.tiers.len() => {
+                    return Some(format!(
+                        "pricing.tiers[{index}] needs up_to_million when later tiers follow"
+                    ));
+                }
+                None => {}
+            }
+        }
+        None
+    }
+
+    fn tiered_cost(&self, millions: f64) -> f64 {
+        let mut cost = 0.0;
+        let mut floor = 0.0;
+        for (index, tier) in self.tiers.iter().enumerate() {
+            let last = index + 1 == self.tiers.len();
+            let ceiling = match tier.up_to_million {
+                Some(up_to) if !last => up_to,
+                // Volume past the final tier is billed at its price
+                _ => f64::INFINITY,
+            };
+            if millions <= floor {
+                break;
+            }
+            cost += (millions.min(ceiling) - floor) * tier.per_million;
+            floor = ceiling;
+        }
+        cost
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn traffic(rps_peak: f64) -> TrafficEstimate {
+        TrafficEstimate::from_profile(&TrafficProfile {
+            rps_peak,
+            global: false,
+            latency_sensitive: false,
+            avg_payload_kb: None,
+            read_ratio: None,
+        })
+    }
+
+    #[test]
+    fn test_traffic_estimate_from_profile() {
+        let estimate = traffic(1000.0);
+        assert!((estimate.monthly_requests - 518_400_000.0).abs() < 1e-3);
+        assert!((estimate.egress_gb - 829.44).abs() < 1e-6);
+
+        let writes_heavy = TrafficEstimate::from_profile(&TrafficProfile {
+            rps_peak: 1000.0,
+            global: false,
+            latency_sensitive: false,
+            avg_payload_kb: Some(10.0),
+            read_ratio: Some(0.5),
+        });
+        assert!((writes_heavy.egress_gb - 2592.0).abs() < 1e-6);
+        assert_eq!(writes_heavy.fraction(TrafficShare::Writes), 0.5);
+    }
+
+    #[test]
+    fn test_tiered_request_pricing() {
+        let pricing = Pricing {
+            tiers: vec![
+                PriceTier {
+                    up_to_million: Some(10.0),
+                    per_million: 0.0,
+                },
+                PriceTier {
+                    up_to_million: Some(1000.0),
+                    per_million: 0.30,
+                },
+                PriceTier {
+                    up_to_million: None,
+                    per_million: 0.05,
+                },
+            ],
+            ..Pricing::default()
+        };
+
+        assert_eq!(pricing.tiered_cost(5.0), 0.0);
+        assert!((pricing.tiered_cost(110.0) - 30.0).abs() < 1e-9);
+        assert!((pricing.tiered_cost(2000.0) - (297.0 + 50.0)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_instance_and_egress_pricing() {
+        let pricing = Pricing {
+            serves: TrafficShare::Reads,
+            instance_rps: Some(300.0),
+            instance_monthly: 10.0,
+            egress_per_gb: 0.05,
+            ..Pricing::default()
+        };
+
+        // 800 read rps need three instances
+        let estimate = traffic(1000.0);
+        let expected = 30.0 + estimate.egress_gb * 0.05;
+        assert!((pricing.monthly_cost(&estimate) - expected).abs() < 1e-9);
+
+        // Idle blueprints still pay for one instance
+        assert_eq!(pricing.monthly_cost(&traffic(0.0)), 10.0);
+    }
+
+    #[test]
+    fn test_pricing_problems() {
+        let tier = |up_to_million, per_million| PriceTier {
+            up_to_million,
+            per_million,
+        };
+        let cases = [
+            (
+                Pricing {
+                    egress_per_gb: -0.1,
+                    ..Pricing::default()
+                },
+                "pricing.egress_per_gb must be a non-negative number, not -0.1",
+            ),
+            (
+                Pricing {
+                    instance_rps: Some(f64::NAN),
+                    ..Pricing::default()
+                },
+                "pricing.instance_rps must be a non-negative number, not NaN",
+            ),
+            (
+                Pricing {
+                    tiers: vec![tier(Some(100.0), 0.0), tier(Some(10.0), 0.3)],
+                    ..Pricing::default()
+                },
+                "pricing.tiers[1].up_to_million must be above the previous tier's 100",
+            ),
+            (
+                Pricing {
+                    tiers: vec![tier(None, 0.0), tier(None, 0.3)],
+                    ..Pricing::default()
+                },
+                "pricing.tiers[0] needs up_to_million when later tiers follow",
+            ),
+        ];
+        for (pricing, message) in cases {
+            assert_eq!(pricing.problem().as_deref(), Some(message));
+        }
+
+        let valid = Pricing {
+            tiers: vec![tier(Some(10.0), 0.0), tier(None, 0.3)],
+            ..Pricing::default()
+        };
+        assert_eq!(valid.problem(), None);
+    }
+}
@@ -0,0 +1,177 @@
+//! Region catalog for hierarchical region matching.
+//!
+//! Rules files may declare a `regions` catalog describing each region's
+//! provider, geography and parent area. Candidate regions and blueprint
+//! `region_allow` entries are matched through it, so an area such as `us`
+//! covers `us-east-1` and everything else nested under it.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// A region or area in the catalog.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct Region {
+    /// Cloud provider hosting the region; areas have none
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+    /// Human-readable geography
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub geography: Option<String>,
+    /// Enclosing area, e.g. `us` for `us-east-1`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<String>,
+}
+
+/// Regions known to the rules, keyed by name.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(transparent)]
+pub struct RegionCatalog {
+    regions: HashMap<String, Region>,
+}
+
+/// Whether a region name stands for every region.
+pub fn is_wildcard(name: &str) -> bool {
+    name == "*" || name == "global"
+}
+
+impl RegionCatalog {
+    pub fn get(&self, name: &str) -> Option<&Region> {
+        self.regions.get(name)
+    }
+
+    /// Names of every region and area in the catalog, in no particular order.
+    pub fn names(&self) -> Vec<&str> {
+        self.regions.keys().map(String::as_str).collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.regions.is_empty()
+    }
+
+    /// Whether a name is a wildcard or present in the catalog.
+    pub fn is_known(&self, name: &str) -> bool {
+        is_wildcard(name) || self.regions.contains_key(name)
+    }
+
+    /// Enclosing areas of a region, nearest first.
+    ///
+    /// Stops at the first repeated name so a cyclic catalog cannot loop.
+    pub fn ancestors<'a>(&'a self, name: &'a str) -> Vec<&'a str> {
+        let mut ancestors: Vec<&str> = Vec::new();
+        let mut current = name;
+        while let Some(parent) = self.get(current).and_then(|r| r.parent.as_deref()) {
+            if parent == name || ancestors.contains(&parent) {
+                break;
+            }
+            ancestors.push(parent);
+            current = parent;
+        }
+        ancestors
+    }
+
+    /// Whether `area` is `region` itself or one of its enclosing areas.
+    pub fn contains(&self, area: &str, region: &str) -> bool {
+        is_wildcard(area) || area == region || self.ancestors(region).contains(&area)
+    }
+
+    /// Whether two region names overlap: one contains the other.
+    pub fn overlaps(&self, a: &str, b: &str) -> bool {
+        self.contains(a, b) || self.contains(b, a)
+    }
+
+    /// Regions covered by both sets, keeping the more specific name of each
+    /// overlapping pair. `None` stands for every region.
+    pub fn intersect(&self, a: Option<&[String]>, b: Option<&[String]>) -> Option<Vec<String>> {
+        let (a, b) = match (a, b) {
+            (None, None) => return None,
+            (Some(set), None) | (None, Some(set)) => return Some(set.to_vec()),
+            (Some(a), Some(b)) => (a, b),
+        };
+
+        let mut shared: Vec<String> = Vec::new();
+        for x in a {
+            for y in b {
+                let narrower = if self.contains(x, y) {
+                    y
+                } else if self.contains(y, x) {
+                    x
+                } else {
+                    continue;
+                };
+                if !shared.contains(narrower) {
+                    shared.push(narrower.clone());
+                }
+            }
+        }
+        Some(shared)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn catalog() -> RegionCatalog {
+        serde_yaml::from_str(
+            r#"
+us: { geography: "United States" }
+us-east: { parent: us }
+us-east-1: { provider: aws, parent: us-east }
+eu: { geography: "Europe" }
+eu-west-1: { provider: aws, parent: eu }
+loop-a: { parent: loop-b }
+loop-b: { parent: loop-a }
+"#,
+        )
+        .unwrap()
+    }
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn test_ancestors_and_containment() {
+        let catalog = catalog();
+        assert_eq!(catalog.ancestors("us-east-1"), vec!["us-east", "us"]);
+        assert!(catalog.contains("us", "us-east-1"));
+        assert!(!catalog.contains("us-east-1", "us"));
+        assert!(catalog.overlaps("us-east-1", "us"));
+        assert!(!catalog.overlaps("us", "eu-west-1"));
+        assert!(catalog.contains("*", "eu-west-1"));
+        assert_eq!(
+            catalog.get("us-east-1").unwrap().provider.as_deref(),
+            Some("aws")
+        );
+    }
+
+    #[test]
+    fn test_cyclic_parents_terminate() {
+        let catalog = catalog();
+        assert_eq!(catalog.ancestors("loop-a"), vec!["loop-b"]);
+        assert!(!catalog.contains("us", "loop-a"));
+    }
+
+    #[test]
+    fn test_intersect_keeps_narrower_regions() {
+        let catalog = catalog();
+        let allowed = names(&["us", "eu"]);
+        let candidate = names(&["us-east-1", "eu-west-1"]);
+        assert_eq!(
+            catalog.intersect(Some(&allowed), Some(&candidate)),
+            Some(names(&["us-east-1", "eu-west-1"]))
+        );
+        assert_eq!(
+            catalog.intersect(Some(&names(&["eu"])), Some(&names(&["us-east-1"]))),
+            Some(Vec::new())
+        );
+        assert_eq!(
+            catalog.intersect(None, Some(&allowed)),
+            Some(allowed.clone())
+        );
+        assert_eq!(catalog.intersect(None, None), None);
+        assert!(catalog.is_known("global"));
+        assert!(!catalog.is_known("mars-1"));
+    }
+}
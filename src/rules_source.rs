@@ -0,0 +1,214 @@
+//! Where the rules come from.
+//!
+//! The first of these wins:
+//!
+//! 1. files given with `--rules`, layered in order;
+//! 2. files listed in `RUNEFORGE_RULES`, separated like `PATH` entries;
+//! 3. `runeforge/rules.yaml` under `$XDG_CONFIG_HOME`, or `~/.config`;
+//! 4. the catalog compiled into the binary.
+
+use crate::error::RuneforgeError;
+use crate::layers;
+use crate::ports::EnvironmentPort;
+use crate::util::calculate_rules_hash;
+use serde_yaml::Value;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Environment variable naming the rules files.
+pub const RULES_ENV: &str = "RUNEFORGE_RULES";
+
+/// Catalog used when no rules file is configured.
+pub const EMBEDDED_RULES: &str = include_str!("../resources/rules.yaml");
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RulesSource {
+    /// Files given with `--rules`
+    Flags(Vec<PathBuf>),
+    /// Files listed in `RUNEFORGE_RULES`
+    Env(Vec<PathBuf>),
+    /// `runeforge/rules.yaml` in the user's config directory
+    Config(PathBuf),
+    /// The catalog compiled into the binary
+    Embedded,
+}
+
+impl RulesSource {
+    /// Pick the rules source for the given `--rules` flags.
+    pub fn locate(flags: &[String], env: &dyn EnvironmentPort) -> Self {
+        if !flags.is_empty() {
+            return RulesSource::Flags(flags.iter().map(PathBuf::from).collect());
+        }
+        if let Ok(value) = env.get_var(RULES_ENV) {
+            let paths: Vec<PathBuf> = std::env::split_paths(&value)
+                .filter(|p| !p.as_os_str().is_empty())
+                .collect();
+            if !paths.is_empty() {
+                return RulesSource::Env(paths);
+            }
+        }
+        match config_file(env) {
+            Some(path) if path.is_file() => RulesSource::Config(path),
+            _ => RulesSource::Embedded,
+        }
+    }
+
+    /// Rules files to layer, empty for the embedded catalog.
+    pub fn files(&self) -> &[PathBuf] {
+        match self {
+            RulesSource::Flags(paths) | RulesSource::Env(paths) => paths,
+            RulesSource::Config(path) => std::slice::from_ref(path),
+            RulesSource::Embedded => &[],
+        }
+    }
+
+    /// Load and merge the rules.
+    pub fn load(&self) -> Result<Value, RuneforgeError> {
+        match self {
+            RulesSource::Embedded => layers::merge_sources(&[EMBEDDED_RULES]),
+            _ => layers::resolve_files(self.files()),
+        }
+    }
+
+    /// Source text when the rules are a single document without `extends`,
+    /// so positions in it mean something to the user.
+    pub fn single_document(&self) -> Result<Option<String>, RuneforgeError> {
+        let path = match self.files() {
+            [] => return Ok(Some(EMBEDDED_RULES.to_string())),
+            [path] => path,
+            _ => return Ok(None),
+        };
+        let source = std::fs::read_to_string(path).map_err(|source| RuneforgeError::Io {
+            action: "read rules file",
+            path: path.display().to_string(),
+            source,
+        })?;
+        Ok((!layers::has_extends(&source)).then_some(source))
+    }
+
+    /// Hash of the effective rules, as `sha256:<hex>`.
+    pub fn hash(&self) -> Result<String, RuneforgeError> {
+        let yaml = serde_yaml::to_string(&self.load()?).map_err(|e| RuneforgeError::Rules {
+            message: e.to_string(),
+        })?;
+        Ok(calculate_rules_hash(&yaml))
+    }
+}
+
+impl fmt::Display for RulesSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let join = |paths: &[PathBuf]| {
+            paths
+                .iter()
+                .map(|p| p.display().to_string())
+                .collect::<Vec<_>>()
+                .join(" + ")
+        };
+        match self {
+            RulesSource::Flags(paths) => write!(f, "{}", join(paths)),
+            RulesSource::Env(paths) => write!(f, "{} (from {RULES_ENV})", join(paths)),
+            RulesSource::Config(path) => write!(f, "{}", path.display()),
+            RulesSource::Embedded => write!(f, "embedded default"),
+        }
+    }
+}
+
+/// `runeforge/rules.yaml` under the XDG config directory.
+fn config_file(env: &dyn EnvironmentPort) -> Option<PathBuf> {
+    let base = match env.get_var("XDG_CONFIG_HOME") {
+        Ok(dir) if Path::new(&dir).is_absolute() => PathBuf::from(dir),
+        _ => PathBuf::from(env.get_var("HOME").ok()?).join(".config"),
+    };
+    Some(base.join("runeforge").join("rules.yaml"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ports::env::EnvError;
+    use std::collections::HashMap;
+    use tempfile::TempDir;
+
+    struct FakeEnv(HashMap<String, String>);
+
+    impl FakeEnv {
+        fn new(vars: &[(&str, &str)]) -> Self {
+            FakeEnv(
+                vars.iter()
+                    .map(|(k, v)| (k.to_string(), v.to_string()))
+                    .collect(),
+            )
+        }
+    }
+
+    impl EnvironmentPort for FakeEnv {
+        fn get_var(&self, key: &str) -> Result<String, EnvError> {
+            self.0
+                .get(key)
+                .cloned()
+                .ok_or_else(|| EnvError::NotFound(key.to_string()))
+        }
+        fn set_var(&self, _key: &str, _value: &str) {}
+        fn remove_var(&self, _key: &str) {}
+        fn current_dir(&self) -> Result<String, EnvError> {
+            Ok("/".to_string())
+        }
+        fn args(&self) -> Vec<String> {
+            Vec::new()
+        }
+    }
+
+    #[test]
+    fn test_flags_win_over_environment() {
+        let env = FakeEnv::new(&[(RULES_ENV, "/etc/runeforge/rules.yaml")]);
+        let flags = vec!["a.yaml".to_string(), "b.yaml".to_string()];
+        let source = RulesSource::locate(&flags, &env);
+        assert_eq!(source.to_string(), "a.yaml + b.yaml");
+
+        let source = RulesSource::locate(&[], &env);
+        assert_eq!(
+            source,
+            RulesSource::Env(vec![PathBuf::from("/etc/runeforge/rules.yaml")])
+        );
+        assert_eq!(
+            source.to_string(),
+            "/etc/runeforge/rules.yaml (from RUNEFORGE_RULES)"
+        );
+    }
+
+    #[test]
+    fn test_config_file_then_embedded() {
+        let dir = TempDir::new().unwrap();
+        let config = dir.path().to_str().unwrap();
+        let env = FakeEnv::new(&[("XDG_CONFIG_HOME", config)]);
+        assert_eq!(RulesSource::locate(&[], &env), RulesSource::Embedded);
+
+        let path = dir.path().join("runeforge").join("rules.yaml");
+        std::fs::create_dir(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, EMBEDDED_RULES).unwrap();
+        assert_eq!(RulesSource::locate(&[], &env), RulesSource::Config(path));
+
+        // Without XDG_CONFIG_HOME the config lives under ~/.config
+        let env = FakeEnv::new(&[("HOME", "/home/rune")]);
+        assert_eq!(
+            config_file(&env),
+            Some(PathBuf::from("/home/rune/.config/runeforge/rules.yaml"))
+        );
+        assert_eq!(config_file(&FakeEnv::new(&[])), None);
+    }
+
+    #[test]
+    fn test_embedded_rules_hash_matches_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("rules.yaml");
+        std::fs::write(&path, EMBEDDED_RULES).unwrap();
+
+        let embedded = RulesSource::Embedded.hash().unwrap();
+        assert!(embedded.starts_with("sha256:"));
+        assert_eq!(RulesSource::Flags(vec![path]).hash().unwrap(), embedded);
+        assert_eq!(
+            RulesSource::Embedded.single_document().unwrap().as_deref(),
+            Some(EMBEDDED_RULES)
+        );
+    }
+}
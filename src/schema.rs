@@ -4,36 +4,127 @@
 //! - Blueprint: Input requirements specification
 //! - StackPlan: Output technology stack recommendations
 
+use crate::error::{location, RuneforgeError};
+use crate::scoring::ScoringStrategy;
+use crate::selector::Rules;
+use jsonschema::error::ValidationErrorKind;
+use jsonschema::{Draft, JSONSchema};
+use schemars::gen::{SchemaGenerator, SchemaSettings};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
 
 /// Blueprint represents the input requirements for technology stack selection.
 ///
 /// A blueprint describes the project requirements, constraints, and preferences
 /// that guide the selection of an optimal technology stack.
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[schemars(deny_unknown_fields)]
 pub struct Blueprint {
+    /// Name of the project
+    #[schemars(length(min = 1))]
     pub project_name: String,
+    /// Project goals and objectives
+    #[schemars(length(min = 1))]
     pub goals: Vec<String>,
     pub constraints: Constraints,
     pub traffic_profile: TrafficProfile,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub prefs: Option<Preferences>,
+    /// Enforce a single language for the entire stack
     #[serde(skip_serializing_if = "Option::is_none")]
     pub single_language_mode: Option<LanguageMode>,
+    /// Services to deploy; the rules' `default_services` when unset
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub services: Option<Vec<ServiceSpec>>,
+    /// Whether each stack category is needed, keyed by category name;
+    /// unlisted categories follow the rules
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub categories: BTreeMap<String, CategoryMode>,
+}
+
+/// Whether a blueprint needs a stack category.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum CategoryMode {
+    /// Leave the category out of the stack
+    None,
+    /// Fill the category, failing when no candidate fits, even if the rules
+    /// make it optional
+    Required,
+    /// Fill the category when the goals or traffic call for it, as the rules'
+    /// `needed` describes
+    Auto,
+}
+
+impl CategoryMode {
+    /// Key used for this mode in blueprints.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CategoryMode::None => "none",
+            CategoryMode::Required => "required",
+            CategoryMode::Auto => "auto",
+        }
+    }
 }
 
 /// Constraints define the limitations and requirements for the technology stack.
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[schemars(deny_unknown_fields)]
 pub struct Constraints {
+    /// Maximum monthly cost in USD
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[schemars(range(min = 0))]
     pub monthly_cost_usd_max: Option<f64>,
+    /// Required persistence type
     #[serde(skip_serializing_if = "Option::is_none")]
     pub persistence: Option<PersistenceType>,
+    /// Allowed deployment regions
     #[serde(skip_serializing_if = "Option::is_none")]
     pub region_allow: Option<Vec<String>>,
+    /// Required compliance standards
     #[serde(skip_serializing_if = "Option::is_none")]
     pub compliance: Option<Vec<ComplianceType>>,
+    /// Minimum quality metric for candidates
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quality_min: Option<MetricFloor>,
+    /// Minimum security metric for candidates
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub security_min: Option<MetricFloor>,
+    /// Minimum SLO metric for candidates
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slo_min: Option<MetricFloor>,
+}
+
+/// Minimum value a candidate metric must reach to be considered.
+///
+/// A bare number applies to every category; a map sets floors for the
+/// named categories only (e.g. `security_min: {database: 0.9}`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum MetricFloor {
+    All(#[schemars(range(min = 0, max = 1))] f64),
+    PerCategory(#[schemars(schema_with = "per_category_floors")] BTreeMap<String, f64>),
+}
+
+fn per_category_floors(_: &mut SchemaGenerator) -> schemars::schema::Schema {
+    serde_json::from_value(serde_json::json!({
+        "type": "object",
+        "additionalProperties": { "type": "number", "minimum": 0.0, "maximum": 1.0 }
+    }))
+    .expect("static schema")
+}
+
+impl MetricFloor {
+    /// Floor that applies to a category, if any.
+    pub fn for_category(&self, category: &str) -> Option<f64> {
+        match self {
+            MetricFloor::All(min) => Some(*min),
+            MetricFloor::PerCategory(floors) => floors.get(category).copied(),
+        }
+    }
 }
 
 /// Type of data persistence required by the application.
@@ -45,6 +136,17 @@ pub enum PersistenceType {
     Both,
 }
 
+impl PersistenceType {
+    /// Key used for this persistence type in blueprints and rules files.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PersistenceType::Kv => "kv",
+            PersistenceType::Sql => "sql",
+            PersistenceType::Both => "both",
+        }
+    }
+}
+
 /// Compliance requirements that the technology stack must support.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
@@ -56,26 +158,92 @@ pub enum ComplianceType {
     Hipaa,
 }
 
+impl ComplianceType {
+    /// Key used for this compliance type in blueprints and rules files.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ComplianceType::AuditLog => "audit-log",
+            ComplianceType::Sbom => "sbom",
+            ComplianceType::Pci => "pci",
+            ComplianceType::Sox => "sox",
+            ComplianceType::Hipaa => "hipaa",
+        }
+    }
+}
+
 /// Traffic characteristics that influence technology selection.
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct TrafficProfile {
+    /// Peak requests per second
+    #[schemars(range(min = 0))]
     pub rps_peak: f64,
+    /// Whether the service needs global deployment
     pub global: bool,
+    /// Whether the service is latency sensitive
     pub latency_sensitive: bool,
+    /// Average response payload in KB, used to estimate egress (default 2)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schemars(range(min = 0))]
+    pub avg_payload_kb: Option<f64>,
+    /// Fraction of requests that are reads (default 0.8)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schemars(range(min = 0, max = 1))]
+    pub read_ratio: Option<f64>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+/// Technologies the team wants and wants to avoid.
+///
+/// Category and service kind keys (`backend`, `cache`, `api`, ...) rank
+/// preferred candidates, most preferred first. Ranked candidates get a score
+/// bonus scaled by `strength`; candidates listed under `avoid` are excluded.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+// Lets the schema of the flattened rankings through as `additionalProperties`
+#[schemars(deny_unknown_fields)]
 pub struct Preferences {
+    #[serde(flatten)]
+    pub ranked: BTreeMap<String, Vec<String>>,
+    /// Candidates to exclude, per category or service kind
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub avoid: BTreeMap<String, Vec<String>>,
+    /// Score bonus for the top-ranked preference (default 0.5)
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub frontend: Option<Vec<String>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub backend: Option<Vec<String>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub database: Option<Vec<String>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub ai: Option<Vec<String>>,
+    #[schemars(range(min = 0, max = 1))]
+    pub strength: Option<f64>,
 }
 
+impl Preferences {
+    /// Bonus strength used when the blueprint does not set one.
+    pub const DEFAULT_STRENGTH: f64 = 0.5;
+
+    /// Preference bonus in `[0, strength]` for the first of `names` ranked
+    /// under `category`; the top entry gets the full strength.
+    pub fn bonus(&self, category: &str, names: &[&str]) -> f64 {
+        let (Some(position), Some(ranked)) =
+            (self.rank(category, names), self.ranked.get(category))
+        else {
+            return 0.0;
+        };
+        let strength = self.strength.unwrap_or(Self::DEFAULT_STRENGTH);
+        strength * (ranked.len() - position) as f64 / ranked.len() as f64
+    }
+
+    /// Position of the first of `names` in the ranking for `category`.
+    pub fn rank(&self, category: &str, names: &[&str]) -> Option<usize> {
+        self.ranked
+            .get(category)?
+            .iter()
+            .position(|p| names.contains(&p.as_str()))
+    }
+
+    /// Whether any of `names` is on the avoid list for `category`.
+    pub fn avoids(&self, category: &str, names: &[&str]) -> bool {
+        self.avoid
+            .get(category)
+            .is_some_and(|avoid| avoid.iter().any(|a| names.contains(&a.as_str())))
+    }
+}
+
+/// Language a single-language stack is restricted to.
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum LanguageMode {
@@ -84,7 +252,149 @@ pub enum LanguageMode {
     Ts,
 }
 
+impl LanguageMode {
+    /// Language candidate name this mode restricts the stack to.
+    pub fn language(&self) -> &'static str {
+        match self {
+            LanguageMode::Rust => "Rust",
+            LanguageMode::Go => "Go",
+            LanguageMode::Ts => "TypeScript",
+        }
+    }
+}
+
+/// A service the project deploys, such as a public API or a scheduler.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct ServiceSpec {
+    /// Unique name, used in decision topics such as `admin-api.framework`
+    #[schemars(length(min = 1))]
+    pub name: String,
+    /// Kind of service, which picks the candidates considered
+    pub kind: ServiceKind,
+    /// Number of instances; the base cost is paid for each (default 1)
+    #[serde(default = "default_service_count")]
+    #[schemars(range(min = 1))]
+    pub count: u32,
+    /// What the service runs; candidates that list workloads must support it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workload: Option<Workload>,
+    #[serde(default, skip_serializing_if = "ServiceConstraints::is_empty")]
+    pub constraints: ServiceConstraints,
+}
+
+fn default_service_count() -> u32 {
+    1
+}
+
+/// Kind of service, matching the candidate lists under `candidates.services`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ServiceKind {
+    Api,
+    Edge,
+    Worker,
+}
+
+impl ServiceKind {
+    /// Key used for this kind in blueprints and rules files.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ServiceKind::Api => "api",
+            ServiceKind::Edge => "edge",
+            ServiceKind::Worker => "worker",
+        }
+    }
+}
+
+/// What a service runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Workload {
+    HttpApi,
+    Websocket,
+    Batch,
+    Cron,
+    StreamConsumer,
+}
+
+impl Workload {
+    /// Key used for this workload in blueprints and rules files.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Workload::HttpApi => "http_api",
+            Workload::Websocket => "websocket",
+            Workload::Batch => "batch",
+            Workload::Cron => "cron",
+            Workload::StreamConsumer => "stream_consumer",
+        }
+    }
+}
+
+/// Constraints for one service. `region_allow` and the metric floors
+/// replace the blueprint's for this service; `compliance` adds to them.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct ServiceConstraints {
+    /// Maximum monthly cost of the service, every instance included
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schemars(range(min = 0))]
+    pub monthly_cost_usd_max: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub region_allow: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compliance: Option<Vec<ComplianceType>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schemars(range(min = 0, max = 1))]
+    pub quality_min: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schemars(range(min = 0, max = 1))]
+    pub security_min: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schemars(range(min = 0, max = 1))]
+    pub slo_min: Option<f64>,
+}
+
+impl ServiceConstraints {
+    pub fn is_empty(&self) -> bool {
+        *self == ServiceConstraints::default()
+    }
+
+    /// The blueprint constraints as they apply to this service.
+    pub fn apply(&self, constraints: &Constraints) -> Constraints {
+        let mut scoped = constraints.clone();
+        if let Some(max_cost) = self.monthly_cost_usd_max {
+            scoped.monthly_cost_usd_max = Some(
+                constraints
+                    .monthly_cost_usd_max
+                    .map_or(max_cost, |m| m.min(max_cost)),
+            );
+        }
+        if let Some(regions) = &self.region_allow {
+            scoped.region_allow = Some(regions.clone());
+        }
+        for compliance in self.compliance.iter().flatten() {
+            let list = scoped.compliance.get_or_insert_with(Vec::new);
+            if !list.contains(compliance) {
+                list.push(compliance.clone());
+            }
+        }
+        for (floor, min) in [
+            (&mut scoped.quality_min, self.quality_min),
+            (&mut scoped.security_min, self.security_min),
+            (&mut scoped.slo_min, self.slo_min),
+        ] {
+            if let Some(min) = min {
+                *floor = Some(MetricFloor::All(min));
+            }
+        }
+        scoped
+    }
+}
+
 // Stack output schema structures
+
+/// Technology stack selection output.
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct StackPlan {
     pub decisions: Vec<Decision>,
@@ -93,115 +403,661 @@ pub struct StackPlan {
     pub meta: Meta,
 }
 
+/// The choice made for one technology category.
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Decision {
+    /// Technology category
     pub topic: String,
+    /// Selected technology
     pub choice: String,
+    /// Reasons for selection
     pub reasons: Vec<String>,
+    /// Alternative technologies considered
     pub alternatives: Vec<String>,
+    /// Selection score
     pub score: f64,
+    /// Score breakdown, present with --explain
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub breakdown: Option<ScoreBreakdown>,
+    /// Candidates removed by constraint filters, present with --explain
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rejected: Vec<Rejection>,
+}
+
+/// A candidate removed by a constraint filter.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Rejection {
+    pub candidate: String,
+    /// Filter that removed the candidate, e.g. `region_allow` or `security_min`
+    pub filter: String,
+    pub required: String,
+    pub actual: String,
+}
+
+impl std::fmt::Display for Rejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {} (required {}, actual {})",
+            self.candidate, self.filter, self.required, self.actual
+        )
+    }
+}
+
+/// How a decision's score was computed.
+///
+/// `score = strategy(metrics) + preference_bonus`; for `weighted_sum` that
+/// is the sum of metric contributions divided by `normalization`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ScoreBreakdown {
+    /// Scoring strategy that combined the metrics
+    #[serde(default)]
+    pub strategy: ScoringStrategy,
+    /// Metrics compared in order by the `lexicographic` strategy
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub priority: Vec<String>,
+    /// Weighted contribution of each metric, with adjusted weights
+    pub metrics: Vec<MetricContribution>,
+    /// Rules adjustments that matched the blueprint, e.g.
+    /// `slo +0.1 when latency_sensitive`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub adjustments: Vec<String>,
+    /// What every weight is divided by: the largest total the rules'
+    /// weights and adjustments can reach
+    pub normalization: f64,
+    /// Added by ranked team preferences
+    pub preference_bonus: f64,
+}
+
+impl ScoreBreakdown {
+    /// Score before preferences are applied.
+    pub fn base_score(&self) -> f64 {
+        let metrics: Vec<(&str, f64, f64)> = self
+            .metrics
+            .iter()
+            .map(|m| (m.metric.as_str(), m.value, m.weight))
+            .collect();
+        self.strategy
+            .score_normalized(&metrics, &self.priority, self.normalization)
+    }
+
+    pub fn score(&self) -> f64 {
+        self.base_score() + self.preference_bonus
+    }
+}
+
+/// One weighted metric in a score breakdown.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct MetricContribution {
+    pub metric: String,
+    pub value: f64,
+    pub weight: f64,
+    /// `weight * value`
+    pub contribution: f64,
 }
 
+/// The chosen technology for every category.
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+// Lets the schema of the flattened categories through as `additionalProperties`
+#[schemars(deny_unknown_fields)]
 pub struct Stack {
+    /// Primary programming language
     pub language: String,
+    /// Services chosen for the stack
     #[serde(skip_serializing_if = "Option::is_none")]
     pub services: Option<Vec<Service>>,
-    pub frontend: String,
-    pub backend: String,
-    pub database: String,
-    pub cache: String,
-    pub queue: String,
-    pub ai: Vec<String>,
-    pub infra: String,
-    pub ci_cd: String,
+    /// Store serving each data role, `sql` and `kv`, when the blueprint
+    /// sets `persistence`; a multi-model store serves both
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data_roles: Option<BTreeMap<String, String>>,
+    /// Choice for every other category in the rules, such as `database`;
+    /// `null` for categories the plan leaves out
+    #[serde(flatten)]
+    pub components: BTreeMap<String, StackEntry>,
 }
 
+impl Stack {
+    /// Names chosen for a category, empty when it is not in the stack.
+    pub fn choices(&self, category: &str) -> Vec<&str> {
+        if category == "language" {
+            return vec![&self.language];
+        }
+        match self.components.get(category) {
+            Some(StackEntry::One(name)) => vec![name],
+            Some(StackEntry::Many(names)) => names.iter().map(String::as_str).collect(),
+            Some(StackEntry::None) | None => Vec::new(),
+        }
+    }
+
+    /// The choice for a category with a single candidate.
+    pub fn choice(&self, category: &str) -> Option<&str> {
+        match self.components.get(category) {
+            _ if category == "language" => Some(&self.language),
+            Some(StackEntry::One(name)) => Some(name),
+            _ => None,
+        }
+    }
+}
+
+/// The choice for one category: a name, a list for `many` categories, or
+/// `null` when the category is not needed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum StackEntry {
+    One(String),
+    Many(Vec<String>),
+    None,
+}
+
+impl From<&str> for StackEntry {
+    fn from(name: &str) -> Self {
+        StackEntry::One(name.to_string())
+    }
+}
+
+impl From<Vec<&str>> for StackEntry {
+    fn from(names: Vec<&str>) -> Self {
+        StackEntry::Many(names.into_iter().map(str::to_string).collect())
+    }
+}
+
+impl std::fmt::Display for StackEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StackEntry::One(name) => write!(f, "{name}"),
+            StackEntry::Many(names) => write!(f, "{}", names.join(", ")),
+            StackEntry::None => write!(f, "none"),
+        }
+    }
+}
+
+/// A deployable service and its toolchain.
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Service {
     pub name: String,
+    /// Service kind, e.g. api, edge or worker
     pub kind: String,
+    /// Number of instances
+    pub count: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub workload: Option<Workload>,
     pub language: String,
     pub framework: String,
     pub runtime: String,
+    /// Build command
     pub build: String,
+    /// Test command
     pub tests: String,
 }
 
+/// Cost and traffic estimates for a plan.
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Estimated {
+    /// Estimated monthly cost in USD
+    #[schemars(range(min = 0))]
     pub monthly_cost_usd: f64,
+    /// Estimated monthly egress in GB
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[schemars(range(min = 0))]
     pub egress_gb: Option<f64>,
+    /// Assumptions behind the estimate
     #[serde(skip_serializing_if = "Option::is_none")]
     pub notes: Option<Vec<String>>,
 }
 
+/// Reproducibility metadata.
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Meta {
+    /// Random seed used for deterministic selection
     pub seed: i64,
+    /// SHA256 hash of blueprint input
     pub blueprint_hash: String,
+    /// SHA256 hash of stack output
     pub plan_hash: String,
 }
 
+/// Constraint changes that make an infeasible blueprint feasible, with the
+/// plan the relaxed blueprint produces.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Relaxation {
+    /// Human-readable changes, e.g. "Raise monthly_cost_usd_max from $50 to $63.12"
+    pub changes: Vec<String>,
+    pub plan: StackPlan,
+}
+
+/// Output of `plan --diagnose` for a blueprint with no feasible stack.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Diagnosis {
+    pub error: String,
+    pub relaxations: Vec<Relaxation>,
+}
+
+/// How strictly documents are checked against the shipped JSON Schemas.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ValidationMode {
+    /// Unknown fields are reported as warnings
+    #[default]
+    Lenient,
+    /// Unknown fields, fields the schemas leave undescribed and blueprint
+    /// fields the Rust types would ignore are errors
+    Strict,
+}
+
+const BLUEPRINT_SCHEMA: &str = include_str!("../schemas/blueprint.schema.json");
+const STACK_SCHEMA: &str = include_str!("../schemas/stack.schema.json");
+const RULES_SCHEMA: &str = include_str!("../schemas/rules.schema.json");
+
+/// A shipped schema, compiled as published and with every object closed.
+struct CompiledSchema {
+    lenient: JSONSchema,
+    strict: JSONSchema,
+}
+
+impl CompiledSchema {
+    fn new(source: &str) -> Self {
+        let schema: Value = serde_json::from_str(source).expect("shipped schema is valid JSON");
+        let compile = |schema: &Value| {
+            JSONSchema::options()
+                .with_draft(Draft::Draft7)
+                .compile(schema)
+                .expect("shipped schema compiles")
+        };
+        CompiledSchema {
+            lenient: compile(&schema),
+            strict: compile(&close_objects(schema)),
+        }
+    }
+
+    fn get(&self, mode: ValidationMode) -> &JSONSchema {
+        match mode {
+            ValidationMode::Lenient => &self.lenient,
+            ValidationMode::Strict => &self.strict,
+        }
+    }
+}
+
+fn blueprint_schema() -> &'static CompiledSchema {
+    static SCHEMA: OnceLock<CompiledSchema> = OnceLock::new();
+    SCHEMA.get_or_init(|| CompiledSchema::new(BLUEPRINT_SCHEMA))
+}
+
+fn stack_schema() -> &'static CompiledSchema {
+    static SCHEMA: OnceLock<CompiledSchema> = OnceLock::new();
+    SCHEMA.get_or_init(|| CompiledSchema::new(STACK_SCHEMA))
+}
+
+fn rules_schema() -> &'static CompiledSchema {
+    static SCHEMA: OnceLock<CompiledSchema> = OnceLock::new();
+    SCHEMA.get_or_init(|| CompiledSchema::new(RULES_SCHEMA))
+}
+
+/// Add `additionalProperties: false` to every object schema that lists
+/// `properties` without saying what else it accepts.
+fn close_objects(schema: Value) -> Value {
+    match schema {
+        Value::Object(map) => {
+            let closes =
+                map.contains_key("properties") && !map.contains_key("additionalProperties");
+            let mut map: serde_json::Map<String, Value> = map
+                .into_iter()
+                .map(|(key, value)| (key, close_objects(value)))
+                .collect();
+            if closes {
+                map.insert("additionalProperties".to_string(), Value::Bool(false));
+            }
+            Value::Object(map)
+        }
+        Value::Array(items) => Value::Array(items.into_iter().map(close_objects).collect()),
+        other => other,
+    }
+}
+
+/// A schema violation at a JSON pointer.
+#[derive(Debug)]
+pub(crate) struct Violation {
+    pub(crate) path: String,
+    pub(crate) message: String,
+}
+
+/// Every violation of the rules schema, split into errors and unknown fields.
+pub(crate) fn rules_schema_violations(instance: &Value) -> (Vec<Violation>, Vec<Violation>) {
+    let mut violations = Vec::new();
+    let mut unknown_fields = Vec::new();
+    if let Err(errors) = rules_schema()
+        .get(ValidationMode::Strict)
+        .validate(instance)
+    {
+        for error in errors {
+            let violation = Violation {
+                path: error.instance_path.to_string(),
+                message: error.to_string(),
+            };
+            match error.kind {
+                ValidationErrorKind::AdditionalProperties { .. } => unknown_fields.push(violation),
+                _ => violations.push(violation),
+            }
+        }
+    }
+    (violations, unknown_fields)
+}
+
+/// Validate a document against a schema, returning the unknown-field
+/// warnings in lenient mode or the first violation.
+fn check_schema(
+    schema: &CompiledSchema,
+    instance: &Value,
+    mode: ValidationMode,
+) -> Result<Vec<String>, Violation> {
+    let errors = match schema.get(mode).validate(instance) {
+        Ok(()) => return Ok(Vec::new()),
+        Err(errors) => errors,
+    };
+
+    let mut warnings = Vec::new();
+    let mut violations = Vec::new();
+    for error in errors {
+        let path = error.instance_path.to_string();
+        let message = error.to_string();
+        let unknown_field = matches!(error.kind, ValidationErrorKind::AdditionalProperties { .. });
+        if unknown_field && mode == ValidationMode::Lenient {
+            warnings.push(format!("Unknown field{}: {message}", location(&path)));
+        } else {
+            violations.push(Violation { path, message });
+        }
+    }
+
+    let more = violations.len().saturating_sub(1);
+    match violations.into_iter().next() {
+        None => Ok(warnings),
+        Some(mut first) => {
+            if more > 0 {
+                let noun = if more == 1 { "violation" } else { "violations" };
+                first.message.push_str(&format!(" ({more} more {noun})"));
+            }
+            Err(first)
+        }
+    }
+}
+
+/// JSON pointers of fields set in `input` but missing from `output`.
+fn dropped_fields(input: &Value, output: &Value, path: &str, dropped: &mut Vec<String>) {
+    match (input, output) {
+        (Value::Object(input), Value::Object(output)) => {
+            for (key, value) in input {
+                if value.is_null() {
+                    continue;
+                }
+                let pointer = format!("{path}/{}", key.replace('~', "~0").replace('/', "~1"));
+                match output.get(key) {
+                    Some(kept) => dropped_fields(value, kept, &pointer, dropped),
+                    None => dropped.push(pointer),
+                }
+            }
+        }
+        (Value::Array(input), Value::Array(output)) => {
+            for (index, (value, kept)) in input.iter().zip(output).enumerate() {
+                dropped_fields(value, kept, &format!("{path}/{index}"), dropped);
+            }
+        }
+        _ => {}
+    }
+}
+
 // Validation functions
-pub fn validate_blueprint(data: &str) -> Result<Blueprint, String> {
+pub fn validate_blueprint(data: &str) -> Result<Blueprint, RuneforgeError> {
+    validate_blueprint_with(data, ValidationMode::Lenient).map(|(blueprint, _)| blueprint)
+}
+
+/// Parse and validate a blueprint, returning it with any schema warnings.
+///
+/// The document is checked against `schemas/blueprint.schema.json`; errors
+/// carry the JSON pointer of the offending value.
+pub fn validate_blueprint_with(
+    data: &str,
+    mode: ValidationMode,
+) -> Result<(Blueprint, Vec<String>), RuneforgeError> {
     // Try to parse as YAML first, then JSON
-    let blueprint: Blueprint = serde_yaml::from_str(data)
+    let document: Value = serde_yaml::from_str(data)
         .or_else(|_| serde_json::from_str(data))
-        .map_err(|e| format!("Failed to parse blueprint: {e}"))?;
+        .map_err(|e| RuneforgeError::BlueprintParse {
+            message: e.to_string(),
+        })?;
 
-    // Validate against schema
-    validate_against_schema(&blueprint)?;
+    let schema_error = |violation: Violation| RuneforgeError::BlueprintSchema {
+        path: violation.path,
+        message: violation.message,
+    };
+    let blueprint = match Blueprint::deserialize(&document) {
+        Ok(blueprint) => blueprint,
+        Err(e) => {
+            // Prefer the schema's account of the problem, which has a location
+            check_schema(blueprint_schema(), &document, mode).map_err(schema_error)?;
+            return Err(RuneforgeError::BlueprintParse {
+                message: e.to_string(),
+            });
+        }
+    };
 
     // Additional validation
     if blueprint.project_name.is_empty() {
-        return Err("project_name cannot be empty".to_string());
+        return Err(RuneforgeError::blueprint_schema(
+            "/project_name",
+            "project_name cannot be empty",
+        ));
     }
 
     if blueprint.goals.is_empty() {
-        return Err("goals cannot be empty".to_string());
+        return Err(RuneforgeError::blueprint_schema(
+            "/goals",
+            "goals cannot be empty",
+        ));
     }
 
     if blueprint.traffic_profile.rps_peak < 0.0 {
-        return Err("rps_peak must be non-negative".to_string());
+        return Err(RuneforgeError::blueprint_schema(
+            "/traffic_profile/rps_peak",
+            "rps_peak must be non-negative",
+        ));
+    }
+
+    if blueprint
+        .traffic_profile
+        .avg_payload_kb
+        .is_some_and(|kb| kb < 0.0)
+    {
+        return Err(RuneforgeError::blueprint_schema(
+            "/traffic_profile/avg_payload_kb",
+            "avg_payload_kb must be non-negative",
+        ));
+    }
+
+    if let Some(ratio) = blueprint.traffic_profile.read_ratio {
+        if !(0.0..=1.0).contains(&ratio) {
+            return Err(RuneforgeError::blueprint_schema(
+                "/traffic_profile/read_ratio",
+                "read_ratio must be between 0 and 1",
+            ));
+        }
     }
 
     if let Some(cost) = blueprint.constraints.monthly_cost_usd_max {
         if cost < 0.0 {
-            return Err("monthly_cost_usd_max must be non-negative".to_string());
+            return Err(RuneforgeError::blueprint_schema(
+                "/constraints/monthly_cost_usd_max",
+                "monthly_cost_usd_max must be non-negative",
+            ));
         }
     }
 
-    Ok(blueprint)
-}
+    if let Some(strength) = blueprint.prefs.as_ref().and_then(|p| p.strength) {
+        if !(0.0..=1.0).contains(&strength) {
+            return Err(RuneforgeError::blueprint_schema(
+                "/prefs/strength",
+                "prefs.strength must be between 0 and 1",
+            ));
+        }
+    }
+
+    let services = blueprint.services.as_deref().unwrap_or_default();
+    for (index, service) in services.iter().enumerate() {
+        if services[..index].iter().any(|s| s.name == service.name) {
+            return Err(RuneforgeError::blueprint_schema(
+                &format!("/services/{index}/name"),
+                format!("service '{}' is declared twice", service.name),
+            ));
+        }
+    }
+
+    let constraints = &blueprint.constraints;
+    for (name, floor) in [
+        ("quality_min", &constraints.quality_min),
+        ("security_min", &constraints.security_min),
+        ("slo_min", &constraints.slo_min),
+    ] {
+        let values = match floor {
+            Some(MetricFloor::All(min)) => vec![*min],
+            Some(MetricFloor::PerCategory(floors)) => floors.values().copied().collect(),
+            None => Vec::new(),
+        };
+        if values.iter().any(|v| !(0.0..=1.0).contains(v)) {
+            return Err(RuneforgeError::blueprint_schema(
+                &format!("/constraints/{name}"),
+                format!("{name} must be between 0 and 1"),
+            ));
+        }
+    }
 
-pub fn validate_stack_plan(plan: &StackPlan) -> Result<(), String> {
     // Validate against schema
-    validate_against_schema(plan)?;
+    let warnings = check_schema(blueprint_schema(), &document, mode).map_err(schema_error)?;
 
+    if mode == ValidationMode::Strict {
+        let parsed =
+            serde_json::to_value(&blueprint).map_err(|e| RuneforgeError::BlueprintParse {
+                message: e.to_string(),
+            })?;
+        let mut dropped = Vec::new();
+        dropped_fields(&document, &parsed, "", &mut dropped);
+        if let Some(path) = dropped.into_iter().next() {
+            return Err(RuneforgeError::BlueprintSchema {
+                message: "field is allowed by the schema but ignored by runeforge".to_string(),
+                path,
+            });
+        }
+    }
+
+    Ok((blueprint, warnings))
+}
+
+pub fn validate_stack_plan(plan: &StackPlan) -> Result<(), RuneforgeError> {
+    validate_stack_plan_with(plan, ValidationMode::Lenient).map(|_| ())
+}
+
+/// Validate a plan against `schemas/stack.schema.json`, returning any
+/// schema warnings.
+///
+/// In strict mode, plan fields the schema does not describe are errors, so
+/// drift between the Rust types and the shipped schema is caught.
+pub fn validate_stack_plan_with(
+    plan: &StackPlan,
+    mode: ValidationMode,
+) -> Result<Vec<String>, RuneforgeError> {
     // Additional validation
     if plan.estimated.monthly_cost_usd < 0.0 {
-        return Err("monthly_cost_usd must be non-negative".to_string());
+        return Err(RuneforgeError::output_schema(
+            "/estimated/monthly_cost_usd",
+            "monthly_cost_usd must be non-negative",
+        ));
     }
 
-    for decision in &plan.decisions {
+    for (index, decision) in plan.decisions.iter().enumerate() {
         if decision.score < 0.0 || decision.score > 1.0 {
-            return Err(format!(
-                "Score for {} must be between 0 and 1",
-                decision.topic
+            return Err(RuneforgeError::output_schema(
+                &format!("/decisions/{index}/score"),
+                format!("Score for {} must be between 0 and 1", decision.topic),
             ));
         }
     }
 
-    Ok(())
+    // Validate against schema
+    let document =
+        serde_json::to_value(plan).map_err(|e| RuneforgeError::output_schema("", e.to_string()))?;
+    check_schema(stack_schema(), &document, mode).map_err(|violation| {
+        RuneforgeError::OutputSchema {
+            path: violation.path,
+            message: violation.message,
+        }
+    })
 }
 
-fn validate_against_schema<T: JsonSchema + Serialize>(_data: &T) -> Result<(), String> {
-    // For now, we'll rely on serde's deserialization validation
-    // In a full implementation, we would use jsonschema crate for runtime validation
-    // against the actual JSON schema files
-    Ok(())
+/// Documents whose JSON Schemas are generated from the Rust types.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchemaKind {
+    Blueprint,
+    Stack,
+    Rules,
+}
+
+impl SchemaKind {
+    pub const ALL: [SchemaKind; 3] = [SchemaKind::Blueprint, SchemaKind::Stack, SchemaKind::Rules];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            SchemaKind::Blueprint => "blueprint",
+            SchemaKind::Stack => "stack",
+            SchemaKind::Rules => "rules",
+        }
+    }
+
+    /// File name of the checked-in schema under `schemas/`.
+    pub fn file_name(self) -> String {
+        format!("{}.schema.json", self.name())
+    }
+
+    /// Generate the draft-07 schema from the Rust types.
+    pub fn generate(self) -> Value {
+        let generator = SchemaSettings::draft07().into_generator();
+        let schema = match self {
+            SchemaKind::Blueprint => generator.into_root_schema_for::<Blueprint>(),
+            SchemaKind::Stack => generator.into_root_schema_for::<StackPlan>(),
+            SchemaKind::Rules => generator.into_root_schema_for::<Rules>(),
+        };
+        serde_json::to_value(schema).expect("generated schemas serialize")
+    }
+}
+
+/// Differences between a generated schema and the one on disk, one line per
+/// JSON pointer.
+pub fn schema_differences(generated: &Value, on_disk: &Value) -> Vec<String> {
+    let mut differences = Vec::new();
+    diff_values(generated, on_disk, "", &mut differences);
+    differences
+}
+
+fn diff_values(expected: &Value, actual: &Value, path: &str, differences: &mut Vec<String>) {
+    let pointer = |key: &str| format!("{path}/{}", key.replace('~', "~0").replace('/', "~1"));
+    match (expected, actual) {
+        (Value::Object(expected), Value::Object(actual)) => {
+            for (key, value) in expected {
+                match actual.get(key) {
+                    Some(found) => diff_values(value, found, &pointer(key), differences),
+                    None => differences.push(format!("{}: missing from file", pointer(key))),
+                }
+            }
+            for key in actual.keys().filter(|k| !expected.contains_key(*k)) {
+                differences.push(format!("{}: not in generated schema", pointer(key)));
+            }
+        }
+        (Value::Array(expected), Value::Array(actual)) if expected.len() == actual.len() => {
+            for (index, (value, found)) in expected.iter().zip(actual).enumerate() {
+                diff_values(value, found, &pointer(&index.to_string()), differences);
+            }
+        }
+        _ if expected != actual => {
+            let location = if path.is_empty() { "/" } else { path };
+            differences.push(format!("{location}: expected {expected}, found {actual}"));
+        }
+        _ => {}
+    }
 }
 
 #[cfg(test)]
@@ -300,7 +1156,10 @@ traffic_profile:
 
         let result = validate_blueprint(yaml);
         assert!(result.is_err());
-        assert!(result.unwrap_err().contains("project_name cannot be empty"));
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("project_name cannot be empty"));
     }
 
     #[test]
@@ -317,7 +1176,10 @@ traffic_profile:
 
         let result = validate_blueprint(yaml);
         assert!(result.is_err());
-        assert!(result.unwrap_err().contains("goals cannot be empty"));
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("goals cannot be empty"));
     }
 
     #[test]
@@ -337,6 +1199,7 @@ traffic_profile:
         assert!(result.is_err());
         assert!(result
             .unwrap_err()
+            .to_string()
             .contains("rps_peak must be non-negative"));
     }
 
@@ -358,6 +1221,7 @@ traffic_profile:
         assert!(result.is_err());
         assert!(result
             .unwrap_err()
+            .to_string()
             .contains("monthly_cost_usd_max must be non-negative"));
     }
 
@@ -372,7 +1236,10 @@ goals:
 
         let result = validate_blueprint(yaml);
         assert!(result.is_err());
-        assert!(result.unwrap_err().contains("Failed to parse blueprint"));
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("Failed to parse blueprint"));
     }
 
     #[test]
@@ -436,6 +1303,105 @@ traffic_profile: { rps_peak: 100, global: false, latency_sensitive: false }
         assert!(compliance.contains(&ComplianceType::Hipaa));
     }
 
+    #[test]
+    fn test_metric_floor_parsing() {
+        let yaml = r#"
+project_name: "test"
+goals: ["test"]
+constraints:
+  quality_min: 0.7
+  security_min: { database: 0.9, infra: 0.85 }
+traffic_profile: { rps_peak: 100, global: false, latency_sensitive: false }
+"#;
+        let result = validate_blueprint(yaml).unwrap();
+        let quality = result.constraints.quality_min.unwrap();
+        assert_eq!(quality, MetricFloor::All(0.7));
+        assert_eq!(quality.for_category("cache"), Some(0.7));
+
+        let security = result.constraints.security_min.unwrap();
+        assert_eq!(security.for_category("database"), Some(0.9));
+        assert_eq!(security.for_category("cache"), None);
+        assert!(result.constraints.slo_min.is_none());
+
+        let out_of_range = r#"
+project_name: "test"
+goals: ["test"]
+constraints:
+  slo_min: { database: 1.5 }
+traffic_profile: { rps_peak: 100, global: false, latency_sensitive: false }
+"#;
+        let err = validate_blueprint(out_of_range).unwrap_err().to_string();
+        assert!(err.contains("slo_min must be between 0 and 1"));
+    }
+
+    #[test]
+    fn test_preferences_parsing() {
+        let yaml = r#"
+project_name: "test"
+goals: ["test"]
+constraints: {}
+traffic_profile: { rps_peak: 100, global: false, latency_sensitive: false }
+prefs:
+  backend: ["Axum", "Actix Web"]
+  cache: ["Redis"]
+  avoid:
+    database: ["MongoDB"]
+  strength: 0.4
+"#;
+        let prefs = validate_blueprint(yaml).unwrap().prefs.unwrap();
+        assert_eq!(prefs.ranked.len(), 2);
+        assert_eq!(prefs.rank("backend", &["Actix Web"]), Some(1));
+        assert!((prefs.bonus("backend", &["Axum"]) - 0.4).abs() < 1e-9);
+        assert!((prefs.bonus("backend", &["Actix Web"]) - 0.2).abs() < 1e-9);
+        assert_eq!(prefs.bonus("cache", &["Memcached"]), 0.0);
+        assert!(prefs.avoids("database", &["MongoDB"]));
+        assert!(!prefs.avoids("cache", &["MongoDB"]));
+
+        let too_strong = yaml.replace("strength: 0.4", "strength: 2");
+        let err = validate_blueprint(&too_strong).unwrap_err().to_string();
+        assert!(err.contains("prefs.strength must be between 0 and 1"));
+    }
+
+    #[test]
+    fn test_service_parsing() {
+        let yaml = r#"
+project_name: "test"
+goals: ["test"]
+constraints: { region_allow: [us], compliance: [sbom] }
+traffic_profile: { rps_peak: 100, global: false, latency_sensitive: false }
+services:
+  - { name: public-api, kind: api, count: 2, workload: websocket }
+  - name: scheduler
+    kind: worker
+    workload: cron
+    constraints: { region_allow: [eu], compliance: [audit-log], slo_min: 0.9 }
+"#;
+        let blueprint = validate_blueprint(yaml).unwrap();
+        let services = blueprint.services.as_ref().unwrap();
+        assert_eq!(services[0].count, 2);
+        assert_eq!(services[0].workload, Some(Workload::Websocket));
+        assert!(services[0].constraints.is_empty());
+        assert_eq!(services[1].count, 1);
+        assert_eq!(services[1].kind, ServiceKind::Worker);
+
+        let scoped = services[1].constraints.apply(&blueprint.constraints);
+        assert_eq!(scoped.region_allow, Some(vec!["eu".to_string()]));
+        assert_eq!(
+            scoped.compliance,
+            Some(vec![ComplianceType::Sbom, ComplianceType::AuditLog])
+        );
+        assert_eq!(scoped.slo_min, Some(MetricFloor::All(0.9)));
+
+        let twice = yaml.replace("name: scheduler", "name: public-api");
+        let err = validate_blueprint(&twice).unwrap_err().to_string();
+        assert_eq!(
+            err,
+            "Invalid blueprint at /services/1/name: service 'public-api' is declared twice"
+        );
+        let none = yaml.replace("count: 2", "count: 0");
+        assert!(validate_blueprint(&none).is_err());
+    }
+
     #[test]
     fn test_language_mode_parsing() {
         let yaml_rust = r#"
@@ -487,18 +1453,23 @@ single_language_mode: ts
                 reasons: vec!["High performance".to_string()],
                 alternatives: vec!["Go".to_string()],
                 score: 0.9,
+                breakdown: None,
+                rejected: Vec::new(),
             }],
             stack: Stack {
                 language: "Rust".to_string(),
                 services: None,
-                frontend: "SvelteKit".to_string(),
-                backend: "Actix Web".to_string(),
-                database: "PostgreSQL".to_string(),
-                cache: "Redis".to_string(),
-                queue: "NATS".to_string(),
-                ai: vec!["RuneSage".to_string()],
-                infra: "Terraform".to_string(),
-                ci_cd: "GitHub Actions".to_string(),
+                data_roles: None,
+                components: BTreeMap::from([
+                    ("frontend".to_string(), "SvelteKit".into()),
+                    ("backend".to_string(), "Actix Web".into()),
+                    ("database".to_string(), "PostgreSQL".into()),
+                    ("cache".to_string(), "Redis".into()),
+                    ("queue".to_string(), "NATS".into()),
+                    ("ai".to_string(), vec!["RuneSage"].into()),
+                    ("infra".to_string(), "Terraform".into()),
+                    ("ci_cd".to_string(), "GitHub Actions".into()),
+                ]),
             },
             estimated: Estimated {
                 monthly_cost_usd: 500.0,
@@ -514,6 +1485,10 @@ single_language_mode: ts
 
         let result = validate_stack_plan(&plan);
         assert!(result.is_ok());
+        assert_eq!(
+            validate_stack_plan_with(&plan, ValidationMode::Strict).unwrap(),
+            Vec::<String>::new()
+        );
     }
 
     #[test]
@@ -523,14 +1498,17 @@ single_language_mode: ts
             stack: Stack {
                 language: "Rust".to_string(),
                 services: None,
-                frontend: "SvelteKit".to_string(),
-                backend: "Actix Web".to_string(),
-                database: "PostgreSQL".to_string(),
-                cache: "Redis".to_string(),
-                queue: "NATS".to_string(),
-                ai: vec!["RuneSage".to_string()],
-                infra: "Terraform".to_string(),
-                ci_cd: "GitHub Actions".to_string(),
+                data_roles: None,
+                components: BTreeMap::from([
+                    ("frontend".to_string(), "SvelteKit".into()),
+                    ("backend".to_string(), "Actix Web".into()),
+                    ("database".to_string(), "PostgreSQL".into()),
+                    ("cache".to_string(), "Redis".into()),
+                    ("queue".to_string(), "NATS".into()),
+                    ("ai".to_string(), vec!["RuneSage"].into()),
+                    ("infra".to_string(), "Terraform".into()),
+                    ("ci_cd".to_string(), "GitHub Actions".into()),
+                ]),
             },
             estimated: Estimated {
                 monthly_cost_usd: -100.0,
@@ -548,6 +1526,7 @@ single_language_mode: ts
         assert!(result.is_err());
         assert!(result
             .unwrap_err()
+            .to_string()
             .contains("monthly_cost_usd must be non-negative"));
     }
 
@@ -560,18 +1539,23 @@ single_language_mode: ts
                 reasons: vec!["High performance".to_string()],
                 alternatives: vec!["Go".to_string()],
                 score: 1.5, // Invalid: > 1.0
+                breakdown: None,
+                rejected: Vec::new(),
             }],
             stack: Stack {
                 language: "Rust".to_string(),
                 services: None,
-                frontend: "SvelteKit".to_string(),
-                backend: "Actix Web".to_string(),
-                database: "PostgreSQL".to_string(),
-                cache: "Redis".to_string(),
-                queue: "NATS".to_string(),
-                ai: vec!["RuneSage".to_string()],
-                infra: "Terraform".to_string(),
-                ci_cd: "GitHub Actions".to_string(),
+                data_roles: None,
+                components: BTreeMap::from([
+                    ("frontend".to_string(), "SvelteKit".into()),
+                    ("backend".to_string(), "Actix Web".into()),
+                    ("database".to_string(), "PostgreSQL".into()),
+                    ("cache".to_string(), "Redis".into()),
+                    ("queue".to_string(), "NATS".into()),
+                    ("ai".to_string(), vec!["RuneSage"].into()),
+                    ("infra".to_string(), "Terraform".into()),
+                    ("ci_cd".to_string(), "GitHub Actions".into()),
+                ]),
             },
             estimated: Estimated {
                 monthly_cost_usd: 500.0,
@@ -589,6 +1573,7 @@ single_language_mode: ts
         assert!(result.is_err());
         assert!(result
             .unwrap_err()
+            .to_string()
             .contains("Score for language must be between 0 and 1"));
 
         // Test negative score
@@ -598,6 +1583,7 @@ single_language_mode: ts
         assert!(result2.is_err());
         assert!(result2
             .unwrap_err()
+            .to_string()
             .contains("Score for language must be between 0 and 1"));
     }
 
@@ -648,4 +1634,136 @@ constraints: {}
 "#;
         assert!(validate_blueprint(yaml3).is_err());
     }
+
+    #[test]
+    fn test_schema_violations_carry_json_pointers() {
+        let yaml = r#"
+project_name: "bad-types"
+goals: ["Test bad types"]
+constraints:
+  monthly_cost_usd_max: "not-a-number"
+traffic_profile: { rps_peak: 100, global: false, latency_sensitive: false }
+"#;
+        match validate_blueprint(yaml).unwrap_err() {
+            RuneforgeError::BlueprintSchema { path, message } => {
+                assert_eq!(path, "/constraints/monthly_cost_usd_max");
+                assert!(message.contains("is not of types \"null\", \"number\""));
+            }
+            other => panic!("Expected BlueprintSchema, got {other:?}"),
+        }
+
+        let yaml = r#"
+project_name: "bad-enum"
+goals: ["Test bad enum"]
+constraints:
+  compliance: ["pci", "gdpr"]
+traffic_profile: { rps_peak: 100, global: false, latency_sensitive: false }
+"#;
+        let err = validate_blueprint(yaml).unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("Invalid blueprint at /constraints/compliance/1:"));
+    }
+
+    #[test]
+    fn test_unknown_fields_warn_unless_strict() {
+        let yaml = r#"
+project_name: "test"
+goals: ["test"]
+constraints: {}
+traffic_profile: { rps_peak: 100, global: false, latency_sensitive: false, burst: 3 }
+owner: "platform-team"
+"#;
+        let (_, warnings) = validate_blueprint_with(yaml, ValidationMode::Lenient).unwrap();
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("'owner' was unexpected"));
+
+        // Strict mode also closes objects the schema leaves open
+        match validate_blueprint_with(yaml, ValidationMode::Strict).unwrap_err() {
+            RuneforgeError::BlueprintSchema { path, message } => {
+                assert_eq!(path, "/traffic_profile");
+                assert!(message.contains("'burst' was unexpected"));
+                assert!(message.ends_with("(1 more violation)"));
+            }
+            other => panic!("Expected BlueprintSchema, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_strict_stack_schema_rejects_undocumented_fields() {
+        let plan = serde_json::json!({
+            "decisions": [],
+            "stack": {
+                "language": "Rust", "frontend": "SvelteKit", "backend": "Axum",
+                "database": "PostgreSQL", "cache": "Redis", "queue": "NATS",
+                "ai": [], "infra": "Vercel", "ci_cd": "GitHub Actions"
+            },
+            "estimated": { "monthly_cost_usd": 10.0, "currency": "USD" },
+            "meta": { "seed": 42, "blueprint_hash": "a", "plan_hash": "b" }
+        });
+
+        let lenient = check_schema(stack_schema(), &plan, ValidationMode::Lenient);
+        assert!(lenient.unwrap().is_empty());
+        let strict = check_schema(stack_schema(), &plan, ValidationMode::Strict);
+        assert_eq!(strict.err().unwrap().path, "/estimated");
+    }
+
+    #[test]
+    fn test_dropped_fields_detect_ignored_input() {
+        let input = serde_json::json!({
+            "constraints": { "region_allow": ["us"], "tier": "gold" },
+            "goals": [{ "name": "a", "weight": 1 }],
+            "single_language_mode": null
+        });
+        let output = serde_json::json!({
+            "constraints": { "region_allow": ["us"] },
+            "goals": [{ "name": "a" }]
+        });
+
+        let mut dropped = Vec::new();
+        dropped_fields(&input, &output, "", &mut dropped);
+        assert_eq!(dropped, vec!["/constraints/tier", "/goals/0/weight"]);
+    }
+
+    #[test]
+    fn test_checked_in_schemas_match_types() {
+        for kind in SchemaKind::ALL {
+            let path = format!(
+                "{}/schemas/{}",
+                env!("CARGO_MANIFEST_DIR"),
+                kind.file_name()
+            );
+            let on_disk: Value =
+                serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+            let differences = schema_differences(&kind.generate(), &on_disk);
+            assert!(
+                differences.is_empty(),
+                "{path} is out of date, run `runeforge schema emit {}`: {differences:?}",
+                kind.name()
+            );
+        }
+    }
+
+    #[test]
+    fn test_schema_differences_use_json_pointers() {
+        let generated = serde_json::json!({
+            "required": ["a", "b"],
+            "properties": { "a/b": { "type": "string" }, "c": { "minimum": 0 } }
+        });
+        let on_disk = serde_json::json!({
+            "required": ["a"],
+            "properties": { "c": { "minimum": 1 }, "d": {} }
+        });
+
+        assert_eq!(
+            schema_differences(&generated, &on_disk),
+            vec![
+                "/properties/a~1b: missing from file",
+                "/properties/c/minimum: expected 0, found 1",
+                "/properties/d: not in generated schema",
+                "/required: expected [\"a\",\"b\"], found [\"a\"]",
+            ]
+        );
+        assert!(schema_differences(&generated, &generated).is_empty());
+    }
 }
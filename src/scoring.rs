@@ -0,0 +1,205 @@
+//! Scoring strategies.
+//!
+//! A strategy turns a candidate's weighted metrics into one score. Metric
+//! values are clamped to `[0, 1]` and weights are divided by their sum, or
+//! by a larger fixed normalization, so every strategy scores within `[0, 1]`:
+//!
+//! - `weighted_sum`: the weighted mean of the metrics;
+//! - `weighted_geometric`: the weighted geometric mean, which drags the score
+//!   down when any one metric is weak;
+//! - `lexicographic`: compares the `priority` metrics in order, to two
+//!   decimal places, and breaks ties with the weighted mean;
+//! - `topsis`: closeness to the ideal point (every metric 1) relative to the
+//!   anti-ideal point (every metric 0), by weighted Euclidean distance.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Steps per metric when comparing lexicographically: values are compared
+/// to two decimal places.
+const LEXICOGRAPHIC_STEPS: f64 = 100.0;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ScoringStrategy {
+    #[default]
+    WeightedSum,
+    WeightedGeometric,
+    Lexicographic,
+    Topsis,
+}
+
+impl ScoringStrategy {
+    /// Score metrics given as `(name, value, weight)`.
+    ///
+    /// `priority` orders the metrics for `lexicographic`; when it is empty
+    /// the metrics are compared by descending weight.
+    pub fn score(&self, metrics: &[(&str, f64, f64)], priority: &[String]) -> f64 {
+        self.score_normalized(metrics, priority, 0.0)
+    }
+
+    /// Score with the weights divided by `normalization`, or by their sum
+    /// when that is larger, so scores compare across weight totals.
+    pub fn score_normalized(
+        &self,
+        metrics: &[(&str, f64, f64)],
+        priority: &[String],
+        normalization: f64,
+    ) -> f64 {
+        let sum: f64 = metrics.iter().map(|(_, _, weight)| weight.max(0.0)).sum();
+        if sum <= 0.0 {
+            return 0.0;
+        }
+        let total = sum.max(normalization);
+        if total <= 0.0 {
+            return 0.0;
+        }
+        let normalized: Vec<(&str, f64, f64)> = metrics
+            .iter()
+            .map(|&(name, value, weight)| (name, value.clamp(0.0, 1.0), weight.max(0.0) / total))
+            .collect();
+
+        let score = match self {
+            ScoringStrategy::WeightedSum => weighted_sum(&normalized),
+            ScoringStrategy::WeightedGeometric => normalized
+                .iter()
+                .map(|(_, value, weight)| value.powf(*weight))
+                .product(),
+            ScoringStrategy::Lexicographic => lexicographic(&normalized, priority),
+            ScoringStrategy::Topsis => {
+                let distance = |target: f64| {
+                    normalized
+                        .iter()
+                        .map(|(_, value, weight)| (weight * (target - value)).powi(2))
+                        .sum::<f64>()
+                        .sqrt()
+                };
+                let (to_ideal, to_worst) = (distance(1.0), distance(0.0));
+                if to_ideal + to_worst > 0.0 {
+                    to_worst / (to_ideal + to_worst)
+                } else {
+                    0.0
+                }
+            }
+        };
+        score.clamp(0.0, 1.0)
+    }
+}
+
+impl std::fmt::Display for ScoringStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            ScoringStrategy::WeightedSum => "weighted_sum",
+            ScoringStrategy::WeightedGeometric => "weighted_geometric",
+            ScoringStrategy::Lexicographic => "lexicographic",
+            ScoringStrategy::Topsis => "topsis",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// How candidates are scored.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct Scoring {
+    #[serde(default)]
+    pub strategy: ScoringStrategy,
+    /// Metrics compared in order by `lexicographic`, e.g. `[security, cost]`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub priority: Vec<String>,
+}
+
+fn weighted_sum(metrics: &[(&str, f64, f64)]) -> f64 {
+    metrics
+        .iter()
+        .map(|(_, value, weight)| weight * value)
+        .sum()
+}
+
+/// Encode the priority metrics as base-101 digits, most significant first,
+/// with the weighted mean as the last digit, so higher digits always win.
+fn lexicographic(metrics: &[(&str, f64, f64)], priority: &[String]) -> f64 {
+    let mut levels: Vec<f64> = if priority.is_empty() {
+        let mut by_weight = metrics.to_vec();
+        by_weight.sort_by(|a, b| b.2.total_cmp(&a.2));
+        by_weight.iter().map(|(_, value, _)| *value).collect()
+    } else {
+        priority
+            .iter()
+            .filter_map(|name| metrics.iter().find(|(m, _, _)| m == name))
+            .map(|(_, value, _)| *value)
+            .collect()
+    };
+    levels = levels
+        .into_iter()
+        .map(|value| (value * LEXICOGRAPHIC_STEPS).round() / LEXICOGRAPHIC_STEPS)
+        .collect();
+    levels.push(weighted_sum(metrics));
+
+    let base = LEXICOGRAPHIC_STEPS + 1.0;
+    let encoded: f64 = levels
+        .iter()
+        .enumerate()
+        .map(|(index, value)| value * LEXICOGRAPHIC_STEPS / base.powi(index as i32 + 1))
+        .sum();
+    // The largest encoding, with every level at 1
+    let maximum = 1.0 - base.powi(-(levels.len() as i32));
+    encoded / maximum
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STRATEGIES: [ScoringStrategy; 4] = [
+        ScoringStrategy::WeightedSum,
+        ScoringStrategy::WeightedGeometric,
+        ScoringStrategy::Lexicographic,
+        ScoringStrategy::Topsis,
+    ];
+
+    #[test]
+    fn test_strategies_score_within_unit_interval() {
+        let perfect = [("quality", 1.0, 0.6), ("security", 1.0, 0.4)];
+        let worst = [("quality", 0.0, 0.6), ("security", 0.0, 0.4)];
+        let invalid = [("quality", 1.5, 0.6), ("security", -0.5, -1.0)];
+        for strategy in STRATEGIES {
+            assert!((strategy.score(&perfect, &[]) - 1.0).abs() < 1e-9);
+            assert_eq!(strategy.score(&worst, &[]), 0.0);
+            let score = strategy.score(&invalid, &[]);
+            assert!((0.0..=1.0).contains(&score), "{strategy}: {score}");
+            assert_eq!(strategy.score(&[], &[]), 0.0);
+            let score = strategy.score_normalized(&perfect, &[], 2.0);
+            assert!((0.0..=1.0).contains(&score), "{strategy}: {score}");
+        }
+        let sum = ScoringStrategy::WeightedSum;
+        assert!((sum.score_normalized(&perfect, &[], 2.0) - 0.5).abs() < 1e-9);
+        assert!((sum.score_normalized(&perfect, &[], 0.5) - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_strategies_rank_differently() {
+        let balanced = [("security", 0.7, 0.5), ("cost", 0.7, 0.5)];
+        let lopsided = [("security", 1.0, 0.5), ("cost", 0.45, 0.5)];
+        let sum = ScoringStrategy::WeightedSum;
+        assert!(sum.score(&lopsided, &[]) > sum.score(&balanced, &[]));
+
+        // One weak metric costs more under the geometric mean
+        let geometric = ScoringStrategy::WeightedGeometric;
+        assert!(geometric.score(&lopsided, &[]) < geometric.score(&balanced, &[]));
+        assert!((geometric.score(&balanced, &[]) - 0.7).abs() < 1e-9);
+
+        // Security first, however cheap the other candidate is
+        let priority = ["security".to_string(), "cost".to_string()];
+        let lexicographic = ScoringStrategy::Lexicographic;
+        let cheap = [("security", 0.69, 0.5), ("cost", 1.0, 0.5)];
+        assert!(lexicographic.score(&balanced, &priority) > lexicographic.score(&cheap, &priority));
+        let cheaper = [("security", 0.7, 0.5), ("cost", 0.71, 0.5)];
+        assert!(
+            lexicographic.score(&cheaper, &priority) > lexicographic.score(&balanced, &priority)
+        );
+
+        let topsis = ScoringStrategy::Topsis;
+        assert!((topsis.score(&balanced, &[]) - 0.7).abs() < 1e-9);
+        assert!(topsis.score(&lopsided, &[]) < topsis.score(&balanced, &[]));
+    }
+}
@@ -3,50 +3,721 @@
 //! This module implements the core selection algorithm that evaluates
 //! technology candidates based on weighted metrics and constraints.
 
+use crate::error::RuneforgeError;
+use crate::layers;
 use crate::observability;
+use crate::pricing::{self, Pricing, TrafficEstimate};
+use crate::regions::{self, RegionCatalog};
 use crate::schema::*;
-use crate::util::{calculate_blueprint_hash, calculate_plan_hash, tie_breaker};
+use crate::scoring::{Scoring, ScoringStrategy};
+use crate::util::{calculate_blueprint_hash, calculate_plan_hash, combinations, tie_breaker};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 /// Rules define the available technology candidates and scoring weights.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Rules {
     pub version: i32,
     pub weights: Weights,
+    /// Metric dimensions scored alongside the built-in metrics
+    #[serde(default)]
+    pub dimensions: Vec<Dimension>,
+    /// Weight changes for matching blueprints; the built-in latency and
+    /// global adjustments when unset
+    #[serde(default = "default_adjustments")]
+    pub adjustments: Vec<Adjustment>,
+    /// Strategy that combines the weighted metrics into a score
+    #[serde(default)]
+    pub scoring: Scoring,
+    /// Services planned when the blueprint declares none; an API, an edge
+    /// service for global traffic and a worker above 10,000 RPS when unset
+    #[serde(default = "builtin_services")]
+    pub default_services: Vec<DefaultService>,
+    /// Stack categories; the nine built-in categories when unset
+    #[serde(default = "default_categories")]
+    pub categories: Vec<Category>,
     pub candidates: CandidateCategories,
     #[serde(default)]
     pub compliance_requirements: HashMap<String, ComplianceRequirement>,
+    #[serde(default)]
+    pub polyglot_penalties: PolyglotPenalties,
+    #[serde(default)]
+    pub regions: RegionCatalog,
+}
+
+impl Rules {
+    /// Reject weights, metrics and costs that are not finite numbers, such
+    /// as `.nan`, which no score or cost can be compared with, and pricing
+    /// curves with negative prices or tiers out of order.
+    pub fn check_finite(&self) -> Result<(), RuneforgeError> {
+        let mut values: Vec<(String, f64)> = Vec::new();
+        for (name, weight) in self.weights.named() {
+            values.push((format!("weights.{name}"), weight));
+        }
+        for dimension in &self.dimensions {
+            values.push((
+                format!("dimensions.{}.weight", dimension.name),
+                dimension.weight,
+            ));
+            values.push((
+                format!("dimensions.{}.default", dimension.name),
+                dimension.default,
+            ));
+        }
+        for adjustment in &self.adjustments {
+            values.push((format!("adjustments ({adjustment})"), adjustment.weight));
+        }
+        let penalties = &self.polyglot_penalties;
+        values.push((
+            "polyglot_penalties.extra_language".to_string(),
+            penalties.extra_language,
+        ));
+        values.push((
+            "polyglot_penalties.extra_toolchain".to_string(),
+            penalties.extra_toolchain,
+        ));
+        values.push((
+            "polyglot_penalties.extra_store".to_string(),
+            penalties.extra_store,
+        ));
+
+        let categories = self
+            .candidates
+            .categories
+            .iter()
+            .flat_map(|(category, candidates)| {
+                candidates.iter().map(move |c| {
+                    (
+                        category.as_str(),
+                        &c.name,
+                        &c.metrics,
+                        c.monthly_cost_base,
+                        &c.pricing,
+                    )
+                })
+            });
+        let services =
+            self.candidates
+                .services
+                .by_kind()
+                .into_iter()
+                .flat_map(|(kind, candidates)| {
+                    candidates
+                        .iter()
+                        .map(move |c| (kind, &c.name, &c.metrics, c.monthly_cost_base, &c.pricing))
+                });
+        let mut pricing_problem = None;
+        for (group, name, metrics, cost, pricing) in categories.chain(services) {
+            for (metric, value) in metrics.named() {
+                values.push((format!("{group} candidate '{name}' metric {metric}"), value));
+            }
+            values.push((
+                format!("{group} candidate '{name}' monthly_cost_base"),
+                cost,
+            ));
+            if pricing_problem.is_none() {
+                pricing_problem = pricing
+                    .as_ref()
+                    .and_then(Pricing::problem)
+                    .map(|problem| format!("{group} candidate '{name}' {problem}"));
+            }
+        }
+
+        let non_finite = values
+            .into_iter()
+            .find(|(_, value)| !value.is_finite())
+            .map(|(field, value)| format!("{field} must be a finite number, not {value}"));
+        match non_finite.or(pricing_problem) {
+            Some(message) => Err(RuneforgeError::Rules { message }),
+            None => Ok(()),
+        }
+    }
+
+    /// Categories in the order they are chosen: each after the categories
+    /// it depends on, otherwise in declaration order.
+    pub fn category_order(&self) -> Result<Vec<&Category>, RuneforgeError> {
+        let error = |message: String| RuneforgeError::Rules { message };
+        for (index, category) in self.categories.iter().enumerate() {
+            if self.categories[..index]
+                .iter()
+                .any(|c| c.name == category.name)
+            {
+                return Err(error(format!(
+                    "category '{}' is declared twice",
+                    category.name
+                )));
+            }
+            if let Some(unknown) = category
+                .depends_on
+                .iter()
+                .find(|d| !self.categories.iter().any(|c| &c.name == *d))
+            {
+                return Err(error(format!(
+                    "category '{}' depends on unknown category '{unknown}'",
+                    category.name
+                )));
+            }
+        }
+
+        let mut order: Vec<&Category> = Vec::with_capacity(self.categories.len());
+        while order.len() < self.categories.len() {
+            let next = self.categories.iter().find(|c| {
+                !order.iter().any(|o| o.name == c.name)
+                    && c.depends_on
+                        .iter()
+                        .all(|d| order.iter().any(|o| &o.name == d))
+            });
+            match next {
+                Some(category) => order.push(category),
+                None => {
+                    let cycle: Vec<&str> = self
+                        .categories
+                        .iter()
+                        .filter(|c| !order.iter().any(|o| o.name == c.name))
+                        .map(|c| c.name.as_str())
+                        .collect();
+                    return Err(error(format!(
+                        "category dependencies form a cycle through {}",
+                        cycle.join(", ")
+                    )));
+                }
+            }
+        }
+        Ok(order)
+    }
+
+    /// Every scored dimension of a candidate as `(name, value, weight)`:
+    /// the built-in metrics, then the declared dimensions, which fall back
+    /// to their default when the candidate does not rate them. Weights
+    /// include the adjustments matching the blueprint and never go below 0.
+    pub fn weighted_metrics<'a>(
+        &'a self,
+        metrics: &Metrics,
+        blueprint: &Blueprint,
+    ) -> Vec<(&'a str, f64, f64)> {
+        let mut weighted: Vec<(&str, f64, f64)> = self
+            .weights
+            .named()
+            .into_iter()
+            .zip(metrics.builtin())
+            .map(|((name, weight), (_, value))| (name, value, weight))
+            .collect();
+        weighted.extend(self.dimensions.iter().map(|dimension| {
+            let value = metrics
+                .dimensions
+                .get(&dimension.name)
+                .copied()
+                .unwrap_or(dimension.default);
+            (dimension.name.as_str(), value, dimension.weight)
+        }));
+
+        let adjustments: Vec<&Adjustment> = self.matching_adjustments(blueprint).collect();
+        for (name, _, weight) in &mut weighted {
+            let adjustment: f64 = adjustments
+                .iter()
+                .filter(|a| a.metric == *name)
+                .map(|a| a.weight)
+                .sum();
+            *weight = (*weight + adjustment).max(0.0);
+        }
+        weighted
+    }
+
+    /// The largest total the weights can reach: every weight plus every
+    /// adjustment that raises one. Scores are divided by it, so a candidate
+    /// scores the same for every blueprint its adjustments leave alone.
+    pub fn weight_ceiling(&self) -> f64 {
+        let weights: f64 = self
+            .weights
+            .named()
+            .iter()
+            .map(|(_, weight)| weight.max(0.0))
+            .chain(self.dimensions.iter().map(|d| d.weight.max(0.0)))
+            .sum();
+        let raises: f64 = self.adjustments.iter().map(|a| a.weight.max(0.0)).sum();
+        weights + raises
+    }
+
+    /// Adjustments whose condition matches the blueprint.
+    pub fn matching_adjustments<'a>(
+        &'a self,
+        blueprint: &'a Blueprint,
+    ) -> impl Iterator<Item = &'a Adjustment> + 'a {
+        self.adjustments
+            .iter()
+            .filter(|a| a.when.matches(blueprint))
+    }
+}
+
+/// How many candidates a category contributes to a stack.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Cardinality {
+    /// Exactly one candidate
+    #[default]
+    One,
+    /// `count` candidates, or fewer when not enough pass the filters
+    Many,
+    /// One candidate, or none when every candidate is filtered out
+    Optional,
+}
+
+/// Blueprint settings that only filter the categories that opt in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CategoryFilter {
+    /// Keep only the candidate named by `single_language_mode`
+    SingleLanguageMode,
+    /// Keep only candidates whose `persistence` is `constraints.persistence`
+    Persistence,
+}
+
+/// A stack category and how the selector fills it.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Category {
+    pub name: String,
+    #[serde(default)]
+    pub cardinality: Cardinality,
+    /// Candidates picked for a `many` category; 2 when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(range(min = 1))]
+    pub count: Option<usize>,
+    /// Categories chosen before this one; candidates name the choice they
+    /// need under `requires`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub filters: Vec<CategoryFilter>,
+    /// When a blueprint marking the category `auto` needs it; always when
+    /// unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub needed: Option<Need>,
+}
+
+impl Category {
+    pub fn new(name: &str) -> Self {
+        Category {
+            name: name.to_string(),
+            cardinality: Cardinality::One,
+            count: None,
+            depends_on: Vec::new(),
+            filters: Vec::new(),
+            needed: None,
+        }
+    }
+
+    /// Number of candidates a `many` category picks.
+    pub fn count(&self) -> usize {
+        self.count.unwrap_or(2)
+    }
+}
+
+/// What makes an `auto` category needed: a goal that mentions one of
+/// `goals`, or a blueprint matching any of `when`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct Need {
+    /// Words or phrases looked for in the goals, ignoring case and plurals
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub goals: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub when: Vec<Condition>,
+}
+
+impl Need {
+    fn new(goals: &[&str], when: Vec<Condition>) -> Self {
+        Need {
+            goals: goals.iter().map(|g| g.to_string()).collect(),
+            when,
+        }
+    }
+
+    /// Why the blueprint needs the category, or why it does not.
+    pub fn assess(&self, blueprint: &Blueprint) -> Result<String, String> {
+        for goal in &blueprint.goals {
+            if let Some(keyword) = self.goals.iter().find(|k| mentions(goal, k)) {
+                return Ok(format!("Needed: goal '{goal}' mentions {keyword}"));
+            }
+        }
+        if let Some(condition) = self.when.iter().find(|c| c.matches(blueprint)) {
+            return Ok(format!("Needed: {condition}"));
+        }
+
+        let mut reason = "Not needed".to_string();
+        if !self.goals.is_empty() {
+            reason.push_str(&format!(": no goal mentions {}", self.goals.join(", ")));
+        }
+        if !self.when.is_empty() {
+            let conditions: Vec<String> = self.when.iter().map(|c| c.to_string()).collect();
+            reason.push_str(if self.goals.is_empty() {
+                ": "
+            } else {
+                ", and "
+            });
+            reason.push_str(&format!("none of [{}] applies", conditions.join("; ")));
+        }
+        Err(reason)
+    }
+}
+
+/// Whether `text` contains the words of `phrase`, ignoring case and a
+/// plural `s` on the last word.
+fn mentions(text: &str, phrase: &str) -> bool {
+    let words = |text: &str| -> Vec<String> {
+        text.split(|c: char| !c.is_alphanumeric())
+            .filter(|w| !w.is_empty())
+            .map(str::to_lowercase)
+            .collect()
+    };
+    let (text, phrase) = (words(text), words(phrase));
+    let Some((last, init)) = phrase.split_last() else {
+        return false;
+    };
+    let plural = format!("{last}s");
+    text.windows(phrase.len()).any(|window| {
+        let (window_last, window_init) = window.split_last().unwrap();
+        window_init == init && (window_last == last || *window_last == plural)
+    })
+}
+
+/// The categories of rules files written before categories were declared.
+fn default_categories() -> Vec<Category> {
+    let needs = default_needs();
+    [
+        "language", "backend", "frontend", "database", "cache", "queue", "ai", "infra", "ci_cd",
+    ]
+    .into_iter()
+    .map(|name| {
+        let mut category = Category::new(name);
+        match name {
+            "language" => category.filters = vec![CategoryFilter::SingleLanguageMode],
+            "backend" => category.depends_on = vec!["language".to_string()],
+            "database" => category.filters = vec![CategoryFilter::Persistence],
+            "ai" => category.cardinality = Cardinality::Many,
+            _ => {}
+        }
+        category.needed = needs
+            .iter()
+            .find(|(category, _)| *category == name)
+            .map(|(_, need)| need.clone());
+        category
+    })
+    .collect()
+}
+
+/// When the built-in frontend, cache, queue and ai categories are needed.
+fn default_needs() -> [(&'static str, Need); 4] {
+    let rps_above = |rps: f64| Condition {
+        rps_peak_above: Some(rps),
+        ..Condition::default()
+    };
+    [
+        (
+            "frontend",
+            Need::new(
+                &[
+                    "web",
+                    "website",
+                    "frontend",
+                    "ui",
+                    "dashboard",
+                    "portal",
+                    "storefront",
+                ],
+                Vec::new(),
+            ),
+        ),
+        (
+            "cache",
+            Need::new(
+                &["cache", "caching"],
+                vec![
+                    Condition {
+                        latency_sensitive: Some(true),
+                        ..Condition::default()
+                    },
+                    rps_above(1000.0),
+                ],
+            ),
+        ),
+        (
+            "queue",
+            Need::new(
+                &["queue", "async", "background", "event", "job", "stream"],
+                vec![rps_above(10000.0)],
+            ),
+        ),
+        (
+            "ai",
+            Need::new(
+                &["ai", "llm", "ml", "chatbot", "assistant", "recommendation"],
+                Vec::new(),
+            ),
+        ),
+    ]
 }
 
 /// Scoring weights for different quality metrics.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Weights {
+    #[schemars(range(min = 0))]
     pub quality: f64,
+    #[schemars(range(min = 0))]
     pub slo: f64,
+    #[schemars(range(min = 0))]
     pub cost: f64,
+    #[schemars(range(min = 0))]
     pub security: f64,
+    #[schemars(range(min = 0))]
     pub ops: f64,
 }
 
+impl Weights {
+    /// Every weight with its metric name.
+    pub fn named(&self) -> [(&'static str, f64); 5] {
+        [
+            ("quality", self.quality),
+            ("slo", self.slo),
+            ("cost", self.cost),
+            ("security", self.security),
+            ("ops", self.ops),
+        ]
+    }
+}
+
+/// Names of the metrics every candidate rates.
+pub const BUILTIN_METRICS: [&str; 5] = ["quality", "slo", "cost", "security", "ops"];
+
+/// An extra metric dimension, such as developer experience or carbon
+/// footprint, that candidates may rate under `metrics`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Dimension {
+    pub name: String,
+    #[schemars(range(min = 0))]
+    pub weight: f64,
+    /// Value used for candidates that do not rate the dimension
+    #[schemars(range(min = 0, max = 1))]
+    pub default: f64,
+}
+
+/// Extra weight for a metric when the blueprint matches `when`, such as
+/// favouring `slo` for latency-sensitive traffic.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Adjustment {
+    pub when: Condition,
+    /// Built-in metric or declared dimension
+    pub metric: String,
+    /// Added to the metric's weight; negative values lower it
+    pub weight: f64,
+}
+
+impl std::fmt::Display for Adjustment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {:+} when {}", self.metric, self.weight, self.when)
+    }
+}
+
+/// Blueprint fields an adjustment or default service is keyed on. Every
+/// field that is set must match.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Condition {
+    /// `traffic_profile.latency_sensitive`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latency_sensitive: Option<bool>,
+    /// `traffic_profile.global`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub global: Option<bool>,
+    /// Whether `constraints.monthly_cost_usd_max` is set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost_capped: Option<bool>,
+    /// Matches when `traffic_profile.rps_peak` is above this
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rps_peak_above: Option<f64>,
+}
+
+impl Condition {
+    fn fields(&self) -> [(&'static str, Option<bool>); 3] {
+        [
+            ("latency_sensitive", self.latency_sensitive),
+            ("global", self.global),
+            ("cost_capped", self.cost_capped),
+        ]
+    }
+
+    /// Whether the condition sets no field, so it always matches.
+    pub fn is_empty(&self) -> bool {
+        self.fields().iter().all(|(_, value)| value.is_none()) && self.rps_peak_above.is_none()
+    }
+
+    pub fn matches(&self, blueprint: &Blueprint) -> bool {
+        let actual = [
+            blueprint.traffic_profile.latency_sensitive,
+            blueprint.traffic_profile.global,
+            blueprint.constraints.monthly_cost_usd_max.is_some(),
+        ];
+        self.fields()
+            .iter()
+            .zip(actual)
+            .all(|((_, expected), actual)| expected.is_none_or(|e| e == actual))
+            && self
+                .rps_peak_above
+                .is_none_or(|rps| blueprint.traffic_profile.rps_peak > rps)
+    }
+}
+
+impl std::fmt::Display for Condition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut terms: Vec<String> = self
+            .fields()
+            .iter()
+            .filter_map(|(name, value)| match value {
+                Some(true) => Some(name.to_string()),
+                Some(false) => Some(format!("not {name}")),
+                None => None,
+            })
+            .collect();
+        if let Some(rps) = self.rps_peak_above {
+            terms.push(format!("rps_peak > {rps}"));
+        }
+        if terms.is_empty() {
+            write!(f, "always")
+        } else {
+            write!(f, "{}", terms.join(" and "))
+        }
+    }
+}
+
+/// The adjustments of rules files written before adjustments were declared.
+fn default_adjustments() -> Vec<Adjustment> {
+    let adjustment = |when: Condition, metric: &str, weight: f64| Adjustment {
+        when,
+        metric: metric.to_string(),
+        weight,
+    };
+    vec![
+        adjustment(
+            Condition {
+                latency_sensitive: Some(true),
+                ..Condition::default()
+            },
+            "slo",
+            0.1,
+        ),
+        adjustment(
+            Condition {
+                global: Some(true),
+                ..Condition::default()
+            },
+            "ops",
+            0.05,
+        ),
+    ]
+}
+
+/// A service planned when the blueprint declares none and `when` matches.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DefaultService {
+    pub name: String,
+    pub kind: ServiceKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workload: Option<Workload>,
+    #[serde(default, skip_serializing_if = "Condition::is_empty")]
+    pub when: Condition,
+}
+
+impl DefaultService {
+    /// The service as if the blueprint had declared it.
+    fn spec(&self) -> ServiceSpec {
+        ServiceSpec {
+            name: self.name.clone(),
+            kind: self.kind,
+            count: 1,
+            workload: self.workload,
+            constraints: ServiceConstraints::default(),
+        }
+    }
+}
+
+/// The services of rules files written before default services were
+/// declared: an API, an edge service for global traffic and a worker above
+/// 10,000 RPS.
+fn builtin_services() -> Vec<DefaultService> {
+    let service = |kind: ServiceKind, workload: Workload, when: Condition| DefaultService {
+        name: kind.as_str().to_string(),
+        kind,
+        workload: Some(workload),
+        when,
+    };
+    vec![
+        service(ServiceKind::Api, Workload::HttpApi, Condition::default()),
+        service(
+            ServiceKind::Edge,
+            Workload::HttpApi,
+            Condition {
+                global: Some(true),
+                ..Condition::default()
+            },
+        ),
+        service(
+            ServiceKind::Worker,
+            Workload::StreamConsumer,
+            Condition {
+                rps_peak_above: Some(10000.0),
+                ..Condition::default()
+            },
+        ),
+    ]
+}
+
+/// Penalties for service combinations that spread across several languages
+/// or build toolchains, and for pairing two stores to cover persistence
+/// `both`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PolyglotPenalties {
+    /// Subtracted for every language beyond the first
+    pub extra_language: f64,
+    /// Subtracted for every build toolchain beyond the first
+    pub extra_toolchain: f64,
+    /// Subtracted from an SQL and KV store pair, for the second store to run
+    #[serde(default = "default_extra_store")]
+    pub extra_store: f64,
+}
+
+fn default_extra_store() -> f64 {
+    0.05
+}
+
+impl Default for PolyglotPenalties {
+    fn default() -> Self {
+        PolyglotPenalties {
+            extra_language: 0.05,
+            extra_toolchain: 0.02,
+            extra_store: default_extra_store(),
+        }
+    }
+}
+
 /// Technology candidates organized by category.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+// Lets the schema of the flattened categories through as `additionalProperties`
+#[schemars(deny_unknown_fields)]
 pub struct CandidateCategories {
     #[serde(default)]
     pub services: ServiceCandidates,
-    pub language: Vec<Candidate>,
-    pub backend: Vec<Candidate>,
-    pub frontend: Vec<Candidate>,
-    pub database: Vec<Candidate>,
-    pub cache: Vec<Candidate>,
-    pub queue: Vec<Candidate>,
-    pub ai: Vec<Candidate>,
-    pub infra: Vec<Candidate>,
-    pub ci_cd: Vec<Candidate>,
+    /// Candidates of every stack category, keyed by category name
+    #[serde(flatten)]
+    pub categories: BTreeMap<String, Vec<Candidate>>,
+}
+
+impl CandidateCategories {
+    /// Candidates of a category, empty when the rules list none.
+    pub fn get(&self, category: &str) -> &[Candidate] {
+        self.categories.get(category).map_or(&[], Vec::as_slice)
+    }
 }
 
 /// Service-specific candidates for Polyglot support
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
 pub struct ServiceCandidates {
     #[serde(default)]
     pub api: Vec<ServiceCandidate>,
@@ -56,8 +727,28 @@ pub struct ServiceCandidates {
     pub worker: Vec<ServiceCandidate>,
 }
 
+impl ServiceCandidates {
+    /// Candidates of a service kind.
+    pub fn get(&self, kind: ServiceKind) -> &[ServiceCandidate] {
+        match kind {
+            ServiceKind::Api => &self.api,
+            ServiceKind::Edge => &self.edge,
+            ServiceKind::Worker => &self.worker,
+        }
+    }
+
+    /// Candidates of every service kind.
+    pub fn by_kind(&self) -> [(&'static str, &[ServiceCandidate]); 3] {
+        [
+            ("api", &self.api),
+            ("edge", &self.edge),
+            ("worker", &self.worker),
+        ]
+    }
+}
+
 /// A service candidate with language, framework, and runtime details
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ServiceCandidate {
     pub name: String,
     pub language: String,
@@ -65,14 +756,52 @@ pub struct ServiceCandidate {
     pub runtime: String,
     pub build: String,
     pub tests: String,
+    /// Package manager or build tool; defaults to the first word of `build`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub toolchain: Option<String>,
     pub metrics: Metrics,
     pub regions: Vec<String>,
     #[serde(default)]
+    #[schemars(range(min = 0))]
     pub monthly_cost_base: f64,
+    /// Capabilities used to satisfy compliance requirements
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub features: Vec<String>,
+    /// Traffic-dependent costs on top of `monthly_cost_base`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pricing: Option<Pricing>,
+    /// Workloads the candidate can run; empty means any
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub workloads: Vec<Workload>,
+}
+
+impl ServiceCandidate {
+    pub fn toolchain(&self) -> &str {
+        match &self.toolchain {
+            Some(toolchain) => toolchain,
+            None => self.build.split_whitespace().next().unwrap_or_default(),
+        }
+    }
+
+    /// The candidate as seen by the constraint filters shared with stack
+    /// categories.
+    fn as_candidate(&self) -> Candidate {
+        Candidate {
+            name: self.name.clone(),
+            requires: None,
+            persistence: None,
+            metrics: self.metrics.clone(),
+            regions: self.regions.clone(),
+            monthly_cost_base: self.monthly_cost_base,
+            notes: Vec::new(),
+            features: self.features.clone(),
+            pricing: self.pricing.clone(),
+        }
+    }
 }
 
 /// A technology candidate with its metrics and constraints.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Candidate {
     pub name: String,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -82,427 +811,1340 @@ pub struct Candidate {
     pub metrics: Metrics,
     pub regions: Vec<String>,
     #[serde(default)]
+    #[schemars(range(min = 0))]
     pub monthly_cost_base: f64,
     #[serde(default)]
     pub notes: Vec<String>,
+    /// Capabilities used to satisfy compliance requirements
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub features: Vec<String>,
+    /// Traffic-dependent costs on top of `monthly_cost_base`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pricing: Option<Pricing>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Requirements {
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub language: Option<String>,
+/// Choices a candidate depends on, keyed by category, such as
+/// `{ language: Rust }`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(transparent)]
+pub struct Requirements(pub BTreeMap<String, String>);
+
+impl Requirements {
+    /// The choice required for a category.
+    pub fn get(&self, category: &str) -> Option<&str> {
+        self.0.get(category).map(String::as_str)
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Candidate metrics, each between 0 and 1.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+// Lets the schema of the flattened dimensions through as `additionalProperties`
+#[schemars(deny_unknown_fields)]
 pub struct Metrics {
+    #[schemars(range(min = 0, max = 1))]
     pub quality: f64,
+    #[schemars(range(min = 0, max = 1))]
     pub slo: f64,
+    #[schemars(range(min = 0, max = 1))]
     pub cost: f64,
+    #[schemars(range(min = 0, max = 1))]
     pub security: f64,
+    #[schemars(range(min = 0, max = 1))]
     pub ops: f64,
+    /// Ratings for dimensions declared in the rules, keyed by name
+    #[serde(flatten)]
+    pub dimensions: BTreeMap<String, f64>,
+}
+
+impl Metrics {
+    /// The built-in metrics with their names.
+    fn builtin(&self) -> [(&'static str, f64); 5] {
+        [
+            ("quality", self.quality),
+            ("slo", self.slo),
+            ("cost", self.cost),
+            ("security", self.security),
+            ("ops", self.ops),
+        ]
+    }
+
+    /// Every metric with its name, built-in metrics first.
+    pub fn named(&self) -> Vec<(&str, f64)> {
+        let mut named = self.builtin().to_vec();
+        named.extend(
+            self.dimensions
+                .iter()
+                .map(|(name, &value)| (name.as_str(), value)),
+        );
+        named
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Features a candidate must provide when the blueprint requests a
+/// compliance type.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ComplianceRequirement {
     pub required_features: Vec<String>,
+    /// Categories checked for the features; empty means every category
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub applies_to: Vec<String>,
 }
 
 #[derive(Debug)]
 pub struct Selector {
     rules: Rules,
+    /// Indices into `rules.categories`, in the order they are chosen
+    order: Vec<usize>,
     seed: u64,
     beam: usize,
+    explain: bool,
 }
 
 #[derive(Debug, Clone)]
 struct ServiceConfiguration {
-    service_type: String,
+    /// Name of the service this configuration fills
+    service: String,
     candidate: ServiceCandidate,
     score: f64,
+    cost: f64,
 }
 
-impl Selector {
-    pub fn new(rules_content: &str, seed: u64, beam: usize) -> Result<Self, String> {
-        let rules: Rules = serde_yaml::from_str(rules_content)
-            .map_err(|e| format!("Failed to parse rules: {e}"))?;
+/// The ranked configurations of one service the blueprint needs.
+#[derive(Debug, Clone)]
+struct ServiceSlot {
+    spec: ServiceSpec,
+    /// Configurations that passed the filters, best first
+    configurations: Vec<ServiceConfiguration>,
+    /// Why the other candidates were filtered out
+    rejections: Vec<Rejection>,
+    /// Whether the blueprint declares the service, rather than the rules'
+    /// `default_services`
+    declared: bool,
+}
+
+impl ServiceSlot {
+    fn cheapest(&self) -> f64 {
+        self.configurations
+            .iter()
+            .map(|c| c.cost)
+            .fold(f64::INFINITY, f64::min)
+    }
+}
+
+/// Cost of the cheapest configuration of every service that has one,
+/// kept free in the budget while the stack categories are searched.
+/// Default services are only counted with `defaults`.
+fn service_reserve(slots: &[ServiceSlot], defaults: bool) -> f64 {
+    slots
+        .iter()
+        .filter(|s| !s.configurations.is_empty() && (defaults || s.declared))
+        .map(ServiceSlot::cheapest)
+        .sum()
+}
 
-        Ok(Selector { rules, seed, beam })
+/// Budget room to try keeping for services, in order: room for every
+/// service, then only for declared ones, so a tight budget drops default
+/// services before it rejects the blueprint.
+fn service_reserves(slots: &[ServiceSlot]) -> Vec<f64> {
+    let (every, declared) = (service_reserve(slots, true), service_reserve(slots, false));
+    if every > declared {
+        vec![every, declared]
+    } else {
+        vec![every]
     }
+}
 
-    pub fn select(&self, blueprint: &Blueprint) -> Result<StackPlan, String> {
-        let mut decisions = Vec::new();
-        let mut total_cost = 0.0;
+/// Decision recording that a category is left out of the stack.
+fn omitted_decision(topic: &str, reason: String) -> Decision {
+    Decision {
+        topic: topic.to_string(),
+        choice: "none".to_string(),
+        reasons: vec![reason],
+        alternatives: Vec::new(),
+        score: 0.0,
+        breakdown: None,
+        rejected: Vec::new(),
+    }
+}
 
-        // Select language first
-        let language = self.select_language(blueprint)?;
-        decisions.push(language.clone());
+/// Whether a category covers persistence `both`, where an SQL store paired
+/// with a KV store competes with every multi-model store.
+fn pairs_stores(category: &Category, blueprint: &Blueprint) -> bool {
+    category.filters.contains(&CategoryFilter::Persistence)
+        && matches!(
+            blueprint.constraints.persistence,
+            Some(PersistenceType::Both)
+        )
+}
 
-        // Select components based on language
-        let backend = self.select_component("backend", blueprint, Some(&language.choice))?;
-        decisions.push(backend.clone());
-        total_cost += self.get_component_cost("backend", &backend.choice);
+/// The role a single-role store is paired with.
+fn other_role(role: &str) -> &'static str {
+    if role == PersistenceType::Sql.as_str() {
+        PersistenceType::Kv.as_str()
+    } else {
+        PersistenceType::Sql.as_str()
+    }
+}
 
-        let frontend = self.select_component("frontend", blueprint, None)?;
-        decisions.push(frontend.clone());
-        total_cost += self.get_component_cost("frontend", &frontend.choice);
+/// Data roles a store serves: both `sql` and `kv` for a multi-model store.
+fn store_roles(persistence: &str) -> Vec<&str> {
+    if persistence == PersistenceType::Both.as_str() {
+        vec![PersistenceType::Sql.as_str(), PersistenceType::Kv.as_str()]
+    } else {
+        vec![persistence]
+    }
+}
 
-        let database = self.select_database(blueprint)?;
-        decisions.push(database.clone());
-        total_cost += self.get_component_cost("database", &database.choice);
+/// A partial or complete assignment of candidates to service types.
+#[derive(Debug, Clone)]
+struct ServiceCombination {
+    services: Vec<ServiceConfiguration>,
+    score: f64,
+    cost: f64,
+}
 
-        let cache = self.select_component("cache", blueprint, None)?;
-        decisions.push(cache.clone());
-        total_cost += self.get_component_cost("cache", &cache.choice);
+/// A candidate that survived filtering, together with its score.
+#[derive(Debug, Clone)]
+struct ScoredCandidate {
+    candidate: Candidate,
+    score: f64,
+}
 
-        let queue = self.select_component("queue", blueprint, None)?;
-        decisions.push(queue.clone());
-        total_cost += self.get_component_cost("queue", &queue.choice);
+/// One way of filling a stack slot during the joint search.
+///
+/// `one` and `optional` categories hold a single candidate, `many`
+/// categories several; an `optional` category left empty holds none.
+#[derive(Debug, Clone)]
+struct StackOption {
+    members: Vec<ScoredCandidate>,
+    /// Contribution to the stack total. Candidates tied with the top score
+    /// share it, so the seeded tie-break order is kept by the search.
+    objective: f64,
+    cost: f64,
+}
 
-        let ai_decision = self.select_ai(blueprint)?;
-        decisions.push(ai_decision.clone());
-        let ai_choices: Vec<String> = ai_decision
-            .choice
-            .split(", ")
-            .map(|s| s.to_string())
-            .collect();
-        for ai in &ai_choices {
-            total_cost += self.get_component_cost("ai", ai);
+impl StackOption {
+    /// The option of an `optional` category with no suitable candidates.
+    fn none() -> Self {
+        StackOption {
+            members: Vec::new(),
+            objective: 0.0,
+            cost: 0.0,
         }
+    }
+
+    fn names(&self) -> Vec<String> {
+        self.members
+            .iter()
+            .map(|m| m.candidate.name.clone())
+            .collect()
+    }
+}
 
-        let infra = self.select_component("infra", blueprint, None)?;
-        decisions.push(infra.clone());
-        total_cost += self.get_component_cost("infra", &infra.choice);
+/// Tolerance used when comparing stack totals in the search.
+const SEARCH_EPSILON: f64 = 1e-9;
+
+/// Partial stacks the search visits before settling for the best stack
+/// found so far.
+const SEARCH_NODE_LIMIT: usize = 1_000_000;
+
+/// Branch-and-bound search for the highest-scoring stack within a budget.
+///
+/// Each level is one slot of a `RankedStack`. Options are visited in rank
+/// order and the incumbent is only replaced by a strictly better total, so
+/// an unconstrained search returns the per-slot favourites.
+struct StackSearch {
+    budget: Option<f64>,
+    best: Option<(f64, Vec<usize>)>,
+    /// Partial stacks visited so far
+    nodes: usize,
+    limit: usize,
+    /// Whether the search stopped at `limit` before proving `best` optimal
+    truncated: bool,
+}
 
-        let ci_cd = self.select_component("ci_cd", blueprint, None)?;
-        let ci_cd_choice = ci_cd.choice.clone();
-        total_cost += self.get_component_cost("ci_cd", &ci_cd_choice);
-        decisions.push(ci_cd);
+impl StackSearch {
+    fn new(budget: Option<f64>) -> Self {
+        StackSearch {
+            budget,
+            best: None,
+            nodes: 0,
+            limit: SEARCH_NODE_LIMIT,
+            truncated: false,
+        }
+    }
 
-        // Sort decisions by score in descending order
-        decisions.sort_by(|a, b| {
-            b.score
-                .partial_cmp(&a.score)
-                .unwrap_or(std::cmp::Ordering::Equal)
-        });
+    fn run(&mut self, ranked: &RankedStack) {
+        let bounds = ranked.bounds();
+        let mut path = Vec::with_capacity(ranked.slots.len());
+        self.descend(ranked, &bounds, &mut path, 0.0, 0.0);
+    }
 
-        // Check cost constraint
-        if let Some(max_cost) = blueprint.constraints.monthly_cost_usd_max {
-            if total_cost > max_cost {
-                return Err(format!(
-                    "No stack found within cost constraint of ${max_cost}"
-                ));
+    fn descend(
+        &mut self,
+        ranked: &RankedStack,
+        bounds: &SearchBounds,
+        path: &mut Vec<usize>,
+        total: f64,
+        cost: f64,
+    ) {
+        let depth = path.len();
+
+        if self.nodes >= self.limit {
+            self.truncated = true;
+            return;
+        }
+        self.nodes += 1;
+
+        if let Some(budget) = self.budget {
+            if cost + bounds.min_cost_tail[depth] > budget + SEARCH_EPSILON {
+                return;
+            }
+        }
+        if let Some((best_total, _)) = &self.best {
+            if total + bounds.max_tail[depth] <= best_total + SEARCH_EPSILON {
+                return;
             }
         }
 
-        // Build services using beam search
-        let services = self.select_services(blueprint, &language.choice)?;
+        if depth == ranked.slots.len() {
+            self.best = Some((total, path.clone()));
+            return;
+        }
 
-        // Build the stack
-        let stack = Stack {
-            language: language.choice,
-            services: Some(services),
-            frontend: frontend.choice,
-            backend: backend.choice,
-            database: database.choice,
-            cache: cache.choice,
-            queue: queue.choice,
-            ai: ai_choices,
-            infra: infra.choice,
-            ci_cd: ci_cd_choice,
-        };
+        for (index, option) in ranked.options(depth, path).iter().enumerate() {
+            path.push(index);
+            self.descend(
+                ranked,
+                bounds,
+                path,
+                total + option.objective,
+                cost + option.cost,
+            );
+            path.pop();
+        }
+    }
+}
 
-        // Calculate hashes
-        let blueprint_json = serde_json::to_string(blueprint).unwrap();
-        let blueprint_hash = calculate_blueprint_hash(&blueprint_json);
+/// Optimistic score and cheapest cost of the slots from each depth on.
+struct SearchBounds {
+    max_tail: Vec<f64>,
+    min_cost_tail: Vec<f64>,
+}
 
-        let plan = StackPlan {
-            decisions,
-            stack: stack.clone(),
-            estimated: Estimated {
-                monthly_cost_usd: total_cost,
-                egress_gb: Some(200.0), // Default estimate
-                notes: Some(vec!["Target regions: us/eu/apac".to_string()]),
-            },
-            meta: Meta {
-                seed: self.seed as i64,
-                blueprint_hash,
-                plan_hash: String::new(), // Will be filled after serialization
-            },
-        };
+/// Ranked options for one category.
+struct RankedSlot<'r> {
+    category: &'r Category,
+    /// Earlier slots whose choices decide this slot's options: the slots it
+    /// depends on and theirs, in stack order
+    context: Vec<usize>,
+    /// Options for each assignment of option indices to the context slots
+    variants: HashMap<Vec<usize>, Vec<StackOption>>,
+    /// Why an `auto` or `none` category is in or out of the stack
+    need: Option<String>,
+}
 
-        // Calculate plan hash
-        let plan_json = serde_json::to_string(&plan).unwrap();
-        let plan_hash = calculate_plan_hash(&plan_json);
+/// Ranked options for every stack slot, computed once per blueprint.
+///
+/// Slots follow the rules' category order, so a search path of option
+/// indices picks one option per slot.
+struct RankedStack<'r> {
+    slots: Vec<RankedSlot<'r>>,
+}
 
-        // Update plan with correct hash
-        let mut final_plan = plan;
-        final_plan.meta.plan_hash = plan_hash;
+impl RankedStack<'_> {
+    /// Options of the slot at `depth` given the options chosen before it.
+    fn options(&self, depth: usize, path: &[usize]) -> &[StackOption] {
+        let slot = &self.slots[depth];
+        let key: Vec<usize> = slot.context.iter().map(|&d| path[d]).collect();
+        slot.variants.get(&key).map_or(&[], Vec::as_slice)
+    }
 
-        Ok(final_plan)
+    /// Every path prefix of length `depth` that assigns the `context`
+    /// slots; the other entries are 0 and never read.
+    fn assignments(&self, context: &[usize], depth: usize) -> Vec<Vec<usize>> {
+        let mut paths = vec![vec![0; depth]];
+        for &slot in context {
+            let mut next = Vec::new();
+            for path in paths {
+                for index in 0..self.options(slot, &path).len() {
+                    let mut path = path.clone();
+                    path[slot] = index;
+                    next.push(path);
+                }
+            }
+            paths = next;
+        }
+        paths
     }
 
-    fn select_language(&self, blueprint: &Blueprint) -> Result<Decision, String> {
-        let candidates = &self.rules.candidates.language;
+    /// Names chosen for the categories the slot at `depth` depends on.
+    fn dependencies(&self, depth: usize, path: &[usize]) -> Vec<(&str, Vec<String>)> {
+        self.slots[depth]
+            .category
+            .depends_on
+            .iter()
+            .filter_map(|name| {
+                let slot = self.slots.iter().position(|s| &s.category.name == name)?;
+                let option = self.options(slot, path).get(path[slot])?;
+                Some((name.as_str(), option.names()))
+            })
+            .collect()
+    }
 
-        // Filter by single language mode if specified
-        let filtered = if let Some(mode) = &blueprint.single_language_mode {
-            let mode_str = match mode {
-                LanguageMode::Rust => "Rust",
-                LanguageMode::Go => "Go",
-                LanguageMode::Ts => "TypeScript",
-            };
-            candidates
-                .iter()
-                .filter(|c| c.name == mode_str)
-                .cloned()
-                .collect()
-        } else {
-            candidates.clone()
-        };
+    fn bounds(&self) -> SearchBounds {
+        let depth = self.slots.len();
+        let mut max_tail = vec![0.0; depth + 1];
+        let mut min_cost_tail = vec![0.0; depth + 1];
+        for (index, slot) in self.slots.iter().enumerate().rev() {
+            let options = slot.variants.values().flatten();
+            let max_objective = options
+                .clone()
+                .map(|o| o.objective)
+                .fold(f64::NEG_INFINITY, f64::max);
+            let min_cost = options.map(|o| o.cost).fold(f64::INFINITY, f64::min);
+            max_tail[index] = max_tail[index + 1] + max_objective;
+            min_cost_tail[index] = min_cost_tail[index + 1] + min_cost;
+        }
+        SearchBounds {
+            max_tail,
+            min_cost_tail,
+        }
+    }
 
-        self.select_best("language", filtered, blueprint, None)
+    /// Cost of the cheapest complete stack, or infinity when there is none.
+    fn cheapest(&self) -> f64 {
+        let bounds = self.bounds();
+        let mut best = f64::INFINITY;
+        self.cheapest_from(&bounds, &mut Vec::new(), 0.0, &mut best);
+        best
     }
 
-    fn select_database(&self, blueprint: &Blueprint) -> Result<Decision, String> {
-        let candidates = &self.rules.candidates.database;
+    fn cheapest_from(
+        &self,
+        bounds: &SearchBounds,
+        path: &mut Vec<usize>,
+        cost: f64,
+        best: &mut f64,
+    ) {
+        let depth = path.len();
+        if cost + bounds.min_cost_tail[depth] >= *best {
+            return;
+        }
+        if depth == self.slots.len() {
+            *best = cost;
+            return;
+        }
 
-        // Filter by persistence type if specified
-        let filtered = if let Some(persistence) = &blueprint.constraints.persistence {
-            let persistence_str = match persistence {
-                PersistenceType::Kv => "kv",
-                PersistenceType::Sql => "sql",
-                PersistenceType::Both => "both",
-            };
-            candidates
-                .iter()
-                .filter(|c| {
-                    c.persistence
-                        .as_ref()
-                        .map(|p| p == persistence_str)
-                        .unwrap_or(false)
-                })
-                .cloned()
-                .collect()
-        } else {
-            candidates.clone()
-        };
-
-        self.select_best("database", filtered, blueprint, None)
+        // Visit cheap options first so the bound prunes early
+        let options = self.options(depth, path);
+        let mut order: Vec<usize> = (0..options.len()).collect();
+        order.sort_by(|a, b| options[*a].cost.total_cmp(&options[*b].cost));
+        for index in order {
+            let option_cost = options[index].cost;
+            path.push(index);
+            self.cheapest_from(bounds, path, cost + option_cost, best);
+            path.pop();
+        }
     }
+}
 
-    fn select_ai(&self, blueprint: &Blueprint) -> Result<Decision, String> {
-        let candidates = &self.rules.candidates.ai;
+/// How a plan was chosen, used to explain runner-up picks.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SearchMode {
+    Best,
+    /// The best stack found before the search hit its node limit
+    Truncated,
+    Pareto,
+}
 
-        // For AI, we select multiple options
-        let mut scored_candidates: Vec<(String, f64)> = candidates
+/// A partial or complete stack on the Pareto frontier.
+///
+/// Metric aggregates are sums over slots, which compare the same way as
+/// averages because stacks share their slots; an empty optional slot adds
+/// nothing.
+#[derive(Debug, Clone)]
+struct FrontierPoint {
+    path: Vec<usize>,
+    score: f64,
+    cost: f64,
+    security: f64,
+    slo: f64,
+    ops: f64,
+}
+
+impl FrontierPoint {
+    fn dominates(&self, other: &FrontierPoint) -> bool {
+        self.score >= other.score - SEARCH_EPSILON
+            && self.cost <= other.cost + SEARCH_EPSILON
+            && self.security >= other.security - SEARCH_EPSILON
+            && self.slo >= other.slo - SEARCH_EPSILON
+            && self.ops >= other.ops - SEARCH_EPSILON
+    }
+}
+
+/// Keep the points not dominated by an earlier or better comparable point.
+///
+/// Points with identical objectives are deduplicated in favour of the first.
+fn retain_non_dominated(
+    points: Vec<FrontierPoint>,
+    comparable: impl Fn(&FrontierPoint, &FrontierPoint) -> bool,
+) -> Vec<FrontierPoint> {
+    let mut kept: Vec<FrontierPoint> = Vec::new();
+    for point in points {
+        if kept
             .iter()
-            .filter(|c| self.check_constraints(c, blueprint))
-            .map(|c| {
-                let score = self.calculate_score(&c.metrics, blueprint);
-                (c.name.clone(), score)
-            })
-            .collect();
+            .any(|k| comparable(k, &point) && k.dominates(&point))
+        {
+            continue;
+        }
+        kept.retain(|k| !comparable(k, &point) || !point.dominates(k));
+        kept.push(point);
+    }
+    kept
+}
 
-        scored_candidates.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+/// Keep `limit` points spread evenly along a sorted frontier, including
+/// its first and last points.
+fn spread(points: Vec<FrontierPoint>, limit: usize) -> Vec<FrontierPoint> {
+    let count = points.len();
+    if count <= limit {
+        return points;
+    }
+    if limit <= 1 {
+        return points.into_iter().take(limit).collect();
+    }
+    let kept: Vec<usize> = (0..limit)
+        .map(|i| (i * (count - 1) + (limit - 1) / 2) / (limit - 1))
+        .collect();
+    points
+        .into_iter()
+        .enumerate()
+        .filter(|(index, _)| kept.contains(index))
+        .map(|(_, point)| point)
+        .collect()
+}
+
+/// Compute the Pareto frontier over the slots of a ranked stack.
+///
+/// All objectives are additive, so the frontier of the whole stack is built
+/// slot by slot from the frontier of the partial stacks. Partial stacks are
+/// only compared when they agree on every choice a later slot depends on,
+/// since they can be completed the same ways.
+fn pareto_frontier(ranked: &RankedStack, budget: Option<f64>) -> Vec<FrontierPoint> {
+    let bounds = ranked.bounds();
+
+    let mut frontier = vec![FrontierPoint {
+        path: Vec::new(),
+        score: 0.0,
+        cost: 0.0,
+        security: 0.0,
+        slo: 0.0,
+        ops: 0.0,
+    }];
+
+    for depth in 0..ranked.slots.len() {
+        let mut next = Vec::new();
+        for point in &frontier {
+            for (index, option) in ranked.options(depth, &point.path).iter().enumerate() {
+                let cost = point.cost + option.cost;
+                if let Some(budget) = budget {
+                    if cost + bounds.min_cost_tail[depth + 1] > budget + SEARCH_EPSILON {
+                        continue;
+                    }
+                }
 
-        if scored_candidates.is_empty() {
-            return Err("No suitable AI candidates found".to_string());
+                let count = option.members.len().max(1) as f64;
+                let mean = |metric: fn(&Metrics) -> f64| {
+                    option
+                        .members
+                        .iter()
+                        .map(|m| metric(&m.candidate.metrics))
+                        .sum::<f64>()
+                        / count
+                };
+
+                let mut path = point.path.clone();
+                path.push(index);
+                next.push(FrontierPoint {
+                    path,
+                    score: point.score + option.objective,
+                    cost,
+                    security: point.security + mean(|m| m.security),
+                    slo: point.slo + mean(|m| m.slo),
+                    ops: point.ops + mean(|m| m.ops),
+                });
+            }
         }
 
-        // Select top 2 AI options
-        let choices: Vec<String> = scored_candidates
+        let mut shared: Vec<usize> = ranked.slots[depth + 1..]
             .iter()
-            .take(2)
-            .map(|(name, _)| name.clone())
+            .flat_map(|s| s.context.iter().copied())
+            .filter(|&d| d <= depth)
             .collect();
+        shared.sort_unstable();
+        shared.dedup();
+        frontier =
+            retain_non_dominated(next, |a, b| shared.iter().all(|&d| a.path[d] == b.path[d]));
+    }
 
-        let alternatives: Vec<String> = scored_candidates
-            .iter()
-            .skip(2)
-            .take(2)
-            .map(|(name, _)| name.clone())
+    frontier
+}
+
+impl Selector {
+    pub fn new(rules_content: &str, seed: u64, beam: usize) -> Result<Self, RuneforgeError> {
+        let rules: Rules =
+            serde_yaml::from_str(rules_content).map_err(|e| RuneforgeError::Rules {
+                message: e.to_string(),
+            })?;
+
+        Selector::from_rules(rules, seed, beam)
+    }
+
+    /// Build a selector from several rules sources, each merged onto the
+    /// ones before it (see [`crate::layers`]).
+    pub fn from_layers(layers: &[&str], seed: u64, beam: usize) -> Result<Self, RuneforgeError> {
+        let rules = layers::into_rules(layers::merge_sources(layers)?)?;
+        Selector::from_rules(rules, seed, beam)
+    }
+
+    /// Build a selector, failing when the categories cannot be ordered or
+    /// lack a `language` category to pick services for.
+    pub fn from_rules(rules: Rules, seed: u64, beam: usize) -> Result<Self, RuneforgeError> {
+        rules.check_finite()?;
+        let order: Vec<usize> = rules
+            .category_order()?
+            .into_iter()
+            .map(|c| {
+                rules
+                    .categories
+                    .iter()
+                    .position(|r| r.name == c.name)
+                    .unwrap()
+            })
             .collect();
 
-        Ok(Decision {
-            topic: "ai".to_string(),
-            choice: choices.join(", "),
-            reasons: vec![
-                "Selected based on quality and cost balance".to_string(),
-                "Multiple AI providers for redundancy".to_string(),
-            ],
-            alternatives,
-            score: scored_candidates[0].1,
+        let language = rules.categories.iter().find(|c| c.name == "language");
+        if language.map(|c| c.cardinality) != Some(Cardinality::One) {
+            return Err(RuneforgeError::Rules {
+                message: "rules need a 'language' category with cardinality one".to_string(),
+            });
+        }
+
+        Ok(Selector {
+            rules,
+            order,
+            seed,
+            beam,
+            explain: false,
         })
     }
 
-    fn select_component(
+    /// Attach a score breakdown to every decision in the plans produced.
+    pub fn with_explain(mut self, explain: bool) -> Self {
+        self.explain = explain;
+        self
+    }
+
+    /// Score candidates with `strategy` instead of the one in the rules.
+    pub fn with_scoring(mut self, strategy: ScoringStrategy) -> Self {
+        self.rules.scoring.strategy = strategy;
+        self
+    }
+
+    /// Categories in the order they are chosen.
+    fn categories(&self) -> impl Iterator<Item = &Category> {
+        self.order
+            .iter()
+            .map(|&index| &self.rules.categories[index])
+    }
+
+    pub fn select(&self, blueprint: &Blueprint) -> Result<StackPlan, RuneforgeError> {
+        let ranked = self.rank_stack(blueprint)?;
+        let services = self.rank_services(blueprint)?;
+
+        // Search all categories jointly so the budget constrains the whole
+        // stack, leaving room for the cheapest services
+        let budget = blueprint.constraints.monthly_cost_usd_max;
+        let mut search = StackSearch::new(None);
+        for reserve in service_reserves(&services) {
+            search = StackSearch::new(budget.map(|b| b - reserve));
+            search.run(&ranked);
+            if search.best.is_some() {
+                break;
+            }
+        }
+
+        let path = match search.best.take() {
+            Some((_, path)) => path,
+            None => return Err(RuneforgeError::no_stack(budget)),
+        };
+
+        let mode = if search.truncated {
+            SearchMode::Truncated
+        } else {
+            SearchMode::Best
+        };
+        self.build_plan(blueprint, &ranked, &services, &path, mode)
+    }
+
+    /// Return the complete stacks that are not dominated on total score,
+    /// monthly cost and the security, SLO and ops aggregates.
+    ///
+    /// Plans are ordered by total score, highest first, then by cost. When
+    /// the frontier holds more than `limit` stacks, `limit` of them spread
+    /// evenly from the highest score to the lowest are returned.
+    pub fn select_pareto(
         &self,
-        topic: &str,
         blueprint: &Blueprint,
-        language: Option<&str>,
-    ) -> Result<Decision, String> {
-        let candidates = match topic {
-            "backend" => &self.rules.candidates.backend,
-            "frontend" => &self.rules.candidates.frontend,
-            "cache" => &self.rules.candidates.cache,
-            "queue" => &self.rules.candidates.queue,
-            "infra" => &self.rules.candidates.infra,
-            "ci_cd" => &self.rules.candidates.ci_cd,
-            _ => return Err(format!("Unknown component type: {topic}")),
+        limit: Option<usize>,
+    ) -> Result<Vec<StackPlan>, RuneforgeError> {
+        let ranked = self.rank_stack(blueprint)?;
+        let services = self.rank_services(blueprint)?;
+        let budget = blueprint.constraints.monthly_cost_usd_max;
+
+        let mut frontier = Vec::new();
+        for reserve in service_reserves(&services) {
+            frontier = pareto_frontier(&ranked, budget.map(|b| b - reserve));
+            if !frontier.is_empty() {
+                break;
+            }
+        }
+        if frontier.is_empty() {
+            return Err(RuneforgeError::no_stack(budget));
+        }
+        frontier.sort_by(|a, b| b.score.total_cmp(&a.score).then(a.cost.total_cmp(&b.cost)));
+        if let Some(limit) = limit {
+            frontier = spread(frontier, limit);
+        }
+
+        let mut plans = Vec::with_capacity(frontier.len());
+        for point in &frontier {
+            let plan = self.build_plan(
+                blueprint,
+                &ranked,
+                &services,
+                &point.path,
+                SearchMode::Pareto,
+            )?;
+            plans.push((point.score, plan));
+        }
+
+        plans.sort_by(|a, b| {
+            b.0.total_cmp(&a.0).then(
+                a.1.estimated
+                    .monthly_cost_usd
+                    .total_cmp(&b.1.estimated.monthly_cost_usd),
+            )
+        });
+
+        Ok(plans.into_iter().map(|(_, plan)| plan).collect())
+    }
+
+    /// Rank the options for every stack slot, failing on the first slot
+    /// that cannot be filled.
+    ///
+    /// A slot that depends on other categories is ranked once for every
+    /// combination of their options. It fails only when no combination
+    /// leaves a suitable candidate; `optional` slots never fail unless the
+    /// blueprint marks them `required`. Categories the blueprint does not
+    /// need get a single empty option.
+    fn rank_stack(&self, blueprint: &Blueprint) -> Result<RankedStack<'_>, RuneforgeError> {
+        for (name, mode) in &blueprint.categories {
+            let path = format!("/categories/{name}");
+            if !self.rules.categories.iter().any(|c| &c.name == name) {
+                return Err(RuneforgeError::blueprint_schema(
+                    &path,
+                    format!("unknown category '{name}'"),
+                ));
+            }
+            if name == "language" && *mode == CategoryMode::None {
+                return Err(RuneforgeError::blueprint_schema(
+                    &path,
+                    "language cannot be none; backends and services are matched against it",
+                ));
+            }
+        }
+
+        let mut ranked = RankedStack { slots: Vec::new() };
+        for category in self.categories() {
+            let mode = blueprint.categories.get(&category.name).copied();
+            let need = match (mode, &category.needed) {
+                (Some(CategoryMode::None), _) => Err(format!(
+                    "Not needed: the blueprint marks {} as none",
+                    category.name
+                )),
+                (Some(CategoryMode::Auto), Some(needed)) => needed.assess(blueprint).map(Some),
+                _ => Ok(None),
+            };
+            let need = match need {
+                Ok(need) => need,
+                Err(reason) => {
+                    ranked.slots.push(RankedSlot {
+                        category,
+                        context: Vec::new(),
+                        variants: HashMap::from([(Vec::new(), vec![StackOption::none()])]),
+                        need: Some(reason),
+                    });
+                    continue;
+                }
+            };
+            let optional = category.cardinality == Cardinality::Optional
+                && mode != Some(CategoryMode::Required);
+
+            let depth = ranked.slots.len();
+            let mut context: Vec<usize> = Vec::new();
+            for name in &category.depends_on {
+                let slot = ranked
+                    .slots
+                    .iter()
+                    .position(|s| &s.category.name == name)
+                    .unwrap();
+                context.push(slot);
+                context.extend(&ranked.slots[slot].context);
+            }
+            context.sort_unstable();
+            context.dedup();
+
+            // Rank against the dependency choices of every context assignment
+            let mut variants = Vec::new();
+            let mut first_error = None;
+            for path in ranked.assignments(&context, depth) {
+                let key: Vec<usize> = context.iter().map(|&d| path[d]).collect();
+                let dependencies: Vec<(&str, Vec<String>)> = category
+                    .depends_on
+                    .iter()
+                    .map(|name| {
+                        let slot = ranked
+                            .slots
+                            .iter()
+                            .position(|s| &s.category.name == name)
+                            .unwrap();
+                        (
+                            name.as_str(),
+                            ranked.options(slot, &path)[path[slot]].names(),
+                        )
+                    })
+                    .collect();
+                match self.rank_category(category, blueprint, &dependencies) {
+                    Ok(options) => variants.push((key, options)),
+                    Err(_) if optional => {
+                        variants.push((key, vec![StackOption::none()]));
+                    }
+                    Err(e) => {
+                        first_error.get_or_insert(e);
+                        variants.push((key, Vec::new()));
+                    }
+                }
+            }
+            if variants.iter().all(|(_, options)| options.is_empty()) {
+                return Err(
+                    first_error.unwrap_or_else(|| RuneforgeError::NoSuitableCandidates {
+                        category: category.name.clone(),
+                        rejections: Vec::new(),
+                    }),
+                );
+            }
+
+            ranked.slots.push(RankedSlot {
+                category,
+                context,
+                variants: variants.into_iter().collect(),
+                need,
+            });
+        }
+
+        Ok(ranked)
+    }
+
+    fn build_plan(
+        &self,
+        blueprint: &Blueprint,
+        ranked: &RankedStack,
+        services: &[ServiceSlot],
+        path: &[usize],
+        mode: SearchMode,
+    ) -> Result<StackPlan, RuneforgeError> {
+        let chosen_options: Vec<&StackOption> = (0..ranked.slots.len())
+            .map(|depth| &ranked.options(depth, path)[path[depth]])
+            .collect();
+
+        let budget = blueprint.constraints.monthly_cost_usd_max;
+        let mut total_cost: f64 = chosen_options.iter().map(|o| o.cost).sum();
+
+        let chosen: Vec<&Candidate> = chosen_options
+            .iter()
+            .flat_map(|option| option.members.iter().map(|m| &m.candidate))
+            .collect();
+        let region_note = match self.target_regions(blueprint, &chosen) {
+            None => "Target regions: any (every component is global)".to_string(),
+            Some(regions) if regions.is_empty() => {
+                "Target regions: none shared by every component".to_string()
+            }
+            Some(regions) => format!("Target regions: {}", regions.join(", ")),
         };
 
-        // Filter by language requirement if applicable
-        let filtered = if let Some(lang) = language {
-            candidates
+        let mut language_choice = String::new();
+        let mut components = BTreeMap::new();
+        let mut data_roles = BTreeMap::new();
+        let mut decisions = Vec::with_capacity(ranked.slots.len());
+        for (depth, (slot, option)) in ranked.slots.iter().zip(&chosen_options).enumerate() {
+            let name = &slot.category.name;
+            // A category that is not needed, or an optional one without
+            // candidates, is recorded as left out
+            if option.members.is_empty() {
+                let reason = slot
+                    .need
+                    .clone()
+                    .unwrap_or_else(|| "Optional, and no candidate passed the filters".to_string());
+                decisions.push(omitted_decision(name, reason));
+                components.insert(name.clone(), StackEntry::None);
+                continue;
+            }
+            let options = ranked.options(depth, path);
+            let names = option.names();
+
+            let mut decision = if slot.category.cardinality == Cardinality::Many {
+                self.build_many_decision(name, options, path[depth], blueprint, mode)
+            } else if pairs_stores(slot.category, blueprint) {
+                let dependencies = ranked.dependencies(depth, path);
+                self.build_store_decision(
+                    name,
+                    options,
+                    path[depth],
+                    blueprint,
+                    &dependencies,
+                    mode,
+                )
+            } else {
+                let dependencies = ranked.dependencies(depth, path);
+                self.build_decision(name, options, path[depth], blueprint, &dependencies, mode)
+            };
+            if let Some(need) = &slot.need {
+                decision.reasons.insert(0, need.clone());
+            }
+            decisions.push(decision);
+
+            if slot.category.filters.contains(&CategoryFilter::Persistence)
+                && blueprint.constraints.persistence.is_some()
+            {
+                for member in &option.members {
+                    let persistence = member.candidate.persistence.as_deref().unwrap_or_default();
+                    for role in store_roles(persistence) {
+                        data_roles.insert(role.to_string(), member.candidate.name.clone());
+                    }
+                }
+            }
+            if slot.category.cardinality == Cardinality::Many || names.len() > 1 {
+                components.insert(name.clone(), StackEntry::Many(names));
+            } else if name == "language" {
+                language_choice = names[0].clone();
+            } else {
+                components.insert(name.clone(), StackEntry::One(names[0].clone()));
+            }
+        }
+
+        // Build services using beam search within what the stack left over
+        let chosen_services =
+            self.select_services(services, &language_choice, budget.map(|b| b - total_cost));
+        let left_out: Vec<&ServiceSlot> = services
+            .iter()
+            .filter(|s| !s.configurations.is_empty())
+            .filter(|s| !chosen_services.iter().any(|c| c.service == s.spec.name))
+            .collect();
+        let mut stack_services = Vec::with_capacity(chosen_services.len());
+        for config in chosen_services {
+            let slot = services
                 .iter()
-                .filter(|c| {
-                    c.requires
-                        .as_ref()
-                        .and_then(|r| r.language.as_ref())
-                        .map(|l| l == lang)
-                        .unwrap_or(true)
-                })
-                .cloned()
-                .collect()
-        } else {
-            candidates.clone()
+                .find(|s| s.spec.name == config.service)
+                .unwrap();
+            decisions.extend(self.build_service_decisions(
+                slot,
+                &config,
+                &language_choice,
+                blueprint,
+            ));
+            total_cost += config.cost;
+            stack_services.push(Service {
+                name: config.service,
+                kind: slot.spec.kind.as_str().to_string(),
+                count: slot.spec.count,
+                workload: slot.spec.workload,
+                language: config.candidate.language,
+                framework: config.candidate.framework,
+                runtime: config.candidate.runtime,
+                build: config.candidate.build,
+                tests: config.candidate.tests,
+            });
+        }
+        if let Some(max_cost) = budget {
+            observability::log_constraint_evaluation("stack_cost", max_cost, total_cost, true);
+        }
+
+        // Sort decisions by score in descending order
+        decisions.sort_by(|a, b| b.score.total_cmp(&a.score));
+
+        let mut notes = vec![region_note];
+        for slot in left_out {
+            notes.push(format!(
+                "No {} service: its cheapest candidate (${:.2}) does not fit the budget left after the stack",
+                slot.spec.name,
+                slot.cheapest()
+            ));
+        }
+        if mode == SearchMode::Truncated {
+            notes.push(format!(
+                "Search stopped after {SEARCH_NODE_LIMIT} partial stacks; this is the best stack found"
+            ));
+        }
+        for slot in services.iter().filter(|s| s.configurations.is_empty()) {
+            let spec = &slot.spec;
+            if slot.rejections.is_empty() {
+                notes.push(format!(
+                    "No {} service: the rules list no {} candidates",
+                    spec.name,
+                    spec.kind.as_str()
+                ));
+                continue;
+            }
+            let reasons: Vec<String> = slot.rejections.iter().map(|r| r.to_string()).collect();
+            notes.push(format!(
+                "No {} service: every candidate was filtered out ({})",
+                spec.name,
+                reasons.join("; ")
+            ));
+        }
+
+        // Build the stack
+        let stack = Stack {
+            language: language_choice,
+            services: Some(stack_services),
+            data_roles: (!data_roles.is_empty()).then_some(data_roles),
+            components,
         };
 
-        self.select_best(topic, filtered, blueprint, language)
+        // Calculate hashes
+        let blueprint_json = serde_json::to_string(blueprint).unwrap();
+        let blueprint_hash = calculate_blueprint_hash(&blueprint_json);
+
+        let traffic = TrafficEstimate::from_profile(&blueprint.traffic_profile);
+        notes.push(format!(
+            "Assumes {:.1}M requests/month ({:.0}% reads) from rps_peak {} at {:.0}% average load",
+            traffic.monthly_requests / 1_000_000.0,
+            traffic.read_ratio * 100.0,
+            blueprint.traffic_profile.rps_peak,
+            pricing::AVERAGE_LOAD_FACTOR * 100.0
+        ));
+
+        let plan = StackPlan {
+            decisions,
+            stack: stack.clone(),
+            estimated: Estimated {
+                monthly_cost_usd: (total_cost * 100.0).round() / 100.0,
+                egress_gb: Some((traffic.egress_gb * 100.0).round() / 100.0),
+                notes: Some(notes),
+            },
+            meta: Meta {
+                seed: self.seed as i64,
+                blueprint_hash,
+                plan_hash: String::new(), // Will be filled after serialization
+            },
+        };
+
+        // Calculate plan hash
+        let plan_json = serde_json::to_string(&plan).unwrap();
+        let plan_hash = calculate_plan_hash(&plan_json);
+
+        // Update plan with correct hash
+        let mut final_plan = plan;
+        final_plan.meta.plan_hash = plan_hash;
+
+        Ok(final_plan)
     }
 
-    fn select_best(
+    /// Rank the options for a category given the names chosen for the
+    /// categories it depends on.
+    fn rank_category(
         &self,
-        topic: &str,
-        candidates: Vec<Candidate>,
+        category: &Category,
         blueprint: &Blueprint,
-        language: Option<&str>,
-    ) -> Result<Decision, String> {
-        // Filter by constraints
-        let mut filtered: Vec<Candidate> = candidates
+        dependencies: &[(&str, Vec<String>)],
+    ) -> Result<Vec<StackOption>, RuneforgeError> {
+        let candidates = self.rules.candidates.get(&category.name);
+        if pairs_stores(category, blueprint) {
+            return self.rank_stores(category, candidates, blueprint, dependencies);
+        }
+        match category.cardinality {
+            Cardinality::Many => self.rank_many(category, candidates, blueprint, dependencies),
+            Cardinality::One | Cardinality::Optional => {
+                self.rank_candidates(category, candidates, blueprint, dependencies)
+            }
+        }
+    }
+
+    /// Rank every combination of `count` candidates, such as a pair of AI
+    /// providers for redundancy, by their mean score.
+    fn rank_many(
+        &self,
+        category: &Category,
+        candidates: &[Candidate],
+        blueprint: &Blueprint,
+        dependencies: &[(&str, Vec<String>)],
+    ) -> Result<Vec<StackOption>, RuneforgeError> {
+        let topic = &category.name;
+        let mut scored: Vec<ScoredCandidate> = self
+            .filter_candidates(
+                category,
+                candidates.iter().cloned(),
+                blueprint,
+                dependencies,
+            )?
             .into_iter()
-            .filter(|c| self.check_constraints(c, blueprint))
+            .map(|c| ScoredCandidate {
+                score: self.apply_preference(
+                    topic,
+                    &[&c.name],
+                    self.calculate_score(&c.metrics, blueprint),
+                    blueprint,
+                ),
+                candidate: c,
+            })
             .collect();
 
-        // Apply preferences if available
-        if let Some(prefs) = &blueprint.prefs {
-            let pref_list = match topic {
-                "frontend" => prefs.frontend.as_ref(),
-                "backend" => prefs.backend.as_ref(),
-                "database" => prefs.database.as_ref(),
-                "ai" => prefs.ai.as_ref(),
-                _ => None,
-            };
-
-            if let Some(pref_names) = pref_list {
-                let preferred: Vec<Candidate> = filtered
-                    .iter()
-                    .filter(|c| pref_names.contains(&c.name))
-                    .cloned()
-                    .collect();
+        scored.sort_by(|a, b| b.score.total_cmp(&a.score));
 
-                if !preferred.is_empty() {
-                    filtered = preferred;
+        // Take as many as are left when fewer than `count` pass the filters
+        let size = category.count().min(scored.len());
+        Ok(combinations(scored.len(), size)
+            .into_iter()
+            .map(|indices| {
+                let members: Vec<ScoredCandidate> =
+                    indices.iter().map(|&i| scored[i].clone()).collect();
+                StackOption {
+                    objective: members.iter().map(|m| m.score).sum::<f64>() / size as f64,
+                    cost: members
+                        .iter()
+                        .map(|m| self.candidate_cost(&m.candidate, blueprint))
+                        .sum(),
+                    members,
                 }
+            })
+            .collect())
+    }
+
+    /// Rank the stores that cover persistence `both`: every multi-model
+    /// store on its own, and every SQL store paired with a KV store. A pair
+    /// scores the mean of its stores less `polyglot_penalties.extra_store`,
+    /// kept within `[0, 1]`, and costs the sum of both.
+    fn rank_stores(
+        &self,
+        category: &Category,
+        candidates: &[Candidate],
+        blueprint: &Blueprint,
+        dependencies: &[(&str, Vec<String>)],
+    ) -> Result<Vec<StackOption>, RuneforgeError> {
+        let singles = self.rank_candidates(category, candidates, blueprint, dependencies)?;
+        let serving = |role: &str| -> Vec<&StackOption> {
+            singles
+                .iter()
+                .filter(|o| o.members[0].candidate.persistence.as_deref() == Some(role))
+                .collect()
+        };
+        let (sql, kv) = (serving("sql"), serving("kv"));
+        let penalty = self.rules.polyglot_penalties.extra_store;
+
+        let mut options: Vec<StackOption> = serving(PersistenceType::Both.as_str())
+            .into_iter()
+            .cloned()
+            .collect();
+        for first in &sql {
+            for second in &kv {
+                options.push(StackOption {
+                    members: vec![first.members[0].clone(), second.members[0].clone()],
+                    objective: ((first.objective + second.objective) / 2.0 - penalty)
+                        .clamp(0.0, 1.0),
+                    cost: first.cost + second.cost,
+                });
             }
         }
-
-        if filtered.is_empty() {
-            return Err(format!("No suitable {topic} candidates found"));
+        if options.is_empty() {
+            // Every store left serves one role, with nothing to pair it with
+            let mut rejections = self.rejections(&category.name, blueprint, dependencies);
+            rejections.extend(singles.iter().map(|o| {
+                let store = &o.members[0].candidate;
+                let role = store.persistence.clone().unwrap_or_default();
+                Rejection {
+                    candidate: store.name.clone(),
+                    filter: "persistence".to_string(),
+                    required: format!("a {} store to pair with", other_role(&role)),
+                    actual: format!("only {role} stores"),
+                }
+            }));
+            return Err(RuneforgeError::NoSuitableCandidates {
+                category: category.name.clone(),
+                rejections,
+            });
         }
 
+        // Stable, so tied stores keep the seeded tie-break order
+        options.sort_by(|a, b| b.objective.total_cmp(&a.objective));
+        Ok(options)
+    }
+
+    /// Filter and score candidates for a topic.
+    ///
+    /// The returned options are ordered best first, with the seeded
+    /// tie-break winner placed ahead of the candidates it tied with.
+    fn rank_candidates(
+        &self,
+        category: &Category,
+        candidates: &[Candidate],
+        blueprint: &Blueprint,
+        dependencies: &[(&str, Vec<String>)],
+    ) -> Result<Vec<StackOption>, RuneforgeError> {
+        let topic = category.name.as_str();
+
+        // Filter by constraints
+        let filtered = self.filter_candidates(
+            category,
+            candidates.iter().cloned(),
+            blueprint,
+            dependencies,
+        )?;
+
         // Score candidates
-        let mut scored: Vec<(Candidate, f64)> = filtered
+        let mut scored: Vec<ScoredCandidate> = filtered
             .into_iter()
             .map(|c| {
-                let score = self.calculate_score(&c.metrics, blueprint);
+                let details = self.score_breakdown(&c.metrics, blueprint);
+                let base = details.base_score();
+                let score = self.apply_preference(topic, &[&c.name], base, blueprint);
 
                 // Log scoring details
-                let breakdown = vec![
-                    (
-                        "quality".to_string(),
-                        self.rules.weights.quality * c.metrics.quality,
-                    ),
-                    ("slo".to_string(), self.rules.weights.slo * c.metrics.slo),
-                    ("cost".to_string(), self.rules.weights.cost * c.metrics.cost),
-                    (
-                        "security".to_string(),
-                        self.rules.weights.security * c.metrics.security,
-                    ),
-                    ("ops".to_string(), self.rules.weights.ops * c.metrics.ops),
-                ];
+                let mut breakdown: Vec<(String, f64)> = details
+                    .metrics
+                    .iter()
+                    .map(|m| (m.metric.clone(), m.contribution))
+                    .collect();
+                if score > base {
+                    breakdown.push(("preference".to_string(), score - base));
+                }
                 observability::log_scoring(topic, &c.name, score, &breakdown);
 
-                (c, score)
+                ScoredCandidate {
+                    candidate: c,
+                    score,
+                }
             })
             .collect();
 
-        scored.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+        scored.sort_by(|a, b| b.score.total_cmp(&a.score));
 
         // Handle ties
-        let top_score = scored[0].1;
+        let top_score = scored[0].score;
         let tied_candidates: Vec<String> = scored
             .iter()
-            .filter(|(_, score)| (*score - top_score).abs() < 0.001)
-            .map(|(c, _)| c.name.clone())
+            .filter(|s| (s.score - top_score).abs() < 0.001)
+            .map(|s| s.candidate.name.clone())
             .collect();
+        let tied_count = tied_candidates.len();
 
-        let choice = if tied_candidates.len() > 1 {
-            tie_breaker(topic, self.seed, tied_candidates)
-        } else {
-            scored[0].0.name.clone()
-        };
+        if tied_count > 1 {
+            let choice = tie_breaker(topic, self.seed, tied_candidates);
+            if let Some(position) = scored.iter().position(|s| s.candidate.name == choice) {
+                let winner = scored.remove(position);
+                scored.insert(0, winner);
+            }
+        }
 
-        // Get the chosen candidate
-        let chosen = scored
-            .iter()
-            .find(|(c, _)| c.name == choice)
-            .map(|(c, s)| (c.clone(), *s))
-            .unwrap();
+        Ok(scored
+            .into_iter()
+            .enumerate()
+            .map(|(rank, s)| StackOption {
+                objective: if rank < tied_count {
+                    top_score
+                } else {
+                    s.score
+                },
+                cost: self.candidate_cost(&s.candidate, blueprint),
+                members: vec![s],
+            })
+            .collect())
+    }
+
+    fn build_decision(
+        &self,
+        topic: &str,
+        options: &[StackOption],
+        chosen_index: usize,
+        blueprint: &Blueprint,
+        dependencies: &[(&str, Vec<String>)],
+        mode: SearchMode,
+    ) -> Decision {
+        let chosen = &options[chosen_index].members[0];
+        let choice = chosen.candidate.name.clone();
 
         // Prepare alternatives
-        let alternatives: Vec<String> = scored
+        let alternatives: Vec<String> = options
             .iter()
-            .filter(|(c, _)| c.name != choice)
+            .enumerate()
+            .filter(|(index, _)| *index != chosen_index)
             .take(3)
-            .map(|(c, _)| c.name.clone())
+            .map(|(_, o)| o.names().join(" + "))
             .collect();
 
         // Generate reasons
         let mut reasons = vec![];
-        if topic == "backend" && language.is_some() {
-            reasons.push(format!("Compatible with {} language", language.unwrap()));
+        for (category, names) in dependencies {
+            reasons.push(format!("Compatible with {} {category}", names.join(", ")));
         }
-        if chosen.1 > 0.8 {
+        if chosen.score > 0.8 {
             reasons.push("High overall score across all metrics".to_string());
         }
-        if blueprint.traffic_profile.latency_sensitive && chosen.0.metrics.slo > 0.85 {
+        if let Some(rank) = blueprint
+            .prefs
+            .as_ref()
+            .and_then(|p| p.rank(topic, &[&choice]))
+        {
+            reasons.push(format!("Ranked #{} in team preferences", rank + 1));
+        }
+        if blueprint.traffic_profile.latency_sensitive && chosen.candidate.metrics.slo > 0.85 {
             reasons.push("Excellent performance for latency-sensitive workload".to_string());
         }
+        if chosen_index > 0 {
+            let top = options[0].names().join(" + ");
+            match (mode, blueprint.constraints.monthly_cost_usd_max) {
+                (SearchMode::Pareto, _) => {
+                    reasons.push(format!(
+                        "Trades overall score against {top} on the Pareto frontier"
+                    ));
+                }
+                (SearchMode::Best | SearchMode::Truncated, Some(max_cost)) => {
+                    reasons.push(format!(
+                        "Preferred over {top} to keep the stack within ${max_cost}/month"
+                    ));
+                }
+                (SearchMode::Best | SearchMode::Truncated, None) => {}
+            }
+        }
 
         // Add compliance reasons if applicable
         if let Some(compliance_types) = &blueprint.constraints.compliance {
             if !compliance_types.is_empty() {
-                if chosen.0.metrics.security > 0.85 {
+                if chosen.candidate.metrics.security > 0.85 {
                     reasons
                         .push("Strong security features for compliance requirements".to_string());
                 }
-                if compliance_types
-                    .iter()
-                    .any(|c| matches!(c, ComplianceType::Hipaa))
-                {
-                    reasons.push("HIPAA-compliant infrastructure support".to_string());
-                }
-                if compliance_types
-                    .iter()
-                    .any(|c| matches!(c, ComplianceType::Sox))
-                {
-                    reasons.push("SOX compliance with audit trail capabilities".to_string());
+                for (compliance, requirement) in self.compliance_requirements(topic, blueprint) {
+                    reasons.push(format!(
+                        "Provides {} for {compliance} compliance",
+                        requirement.required_features.join(", ")
+                    ));
                 }
             }
         }
 
-        if let Some(notes) = chosen.0.notes.first() {
+        if let Some(notes) = chosen.candidate.notes.first() {
             reasons.push(notes.clone());
         }
 
@@ -511,245 +2153,869 @@ impl Selector {
             reasons.push(format!("Selected based on optimal {topic} score"));
         }
 
-        Ok(Decision {
+        Decision {
             topic: topic.to_string(),
             choice,
             reasons,
             alternatives,
-            score: chosen.1,
-        })
+            score: chosen.score,
+            breakdown: self.explain_score(&chosen.candidate.metrics, chosen.score, blueprint),
+            rejected: if self.explain {
+                self.rejections(topic, blueprint, dependencies)
+            } else {
+                Vec::new()
+            },
+        }
+    }
+
+    /// Decision for a category covering persistence `both`, naming the data
+    /// role each chosen store serves.
+    fn build_store_decision(
+        &self,
+        topic: &str,
+        options: &[StackOption],
+        chosen_index: usize,
+        blueprint: &Blueprint,
+        dependencies: &[(&str, Vec<String>)],
+        mode: SearchMode,
+    ) -> Decision {
+        let chosen = &options[chosen_index];
+        let mut decision =
+            self.build_decision(topic, options, chosen_index, blueprint, dependencies, mode);
+
+        let roles: Vec<String> = chosen
+            .members
+            .iter()
+            .map(|m| {
+                let persistence = m.candidate.persistence.as_deref().unwrap_or_default();
+                format!(
+                    "{} serves {} data",
+                    m.candidate.name,
+                    store_roles(persistence).join(" and ")
+                )
+            })
+            .collect();
+        decision.reasons.insert(0, roles.join("; "));
+
+        if chosen.members.len() > 1 {
+            let score = (chosen.members.iter().map(|m| m.score).sum::<f64>()
+                / chosen.members.len() as f64
+                - self.rules.polyglot_penalties.extra_store)
+                .clamp(0.0, 1.0);
+            decision.choice = chosen.names().join(", ");
+            decision.score = score;
+            // The breakdown describes a single store
+            decision.breakdown = None;
+            decision
+                .reasons
+                .retain(|r| r != "High overall score across all metrics");
+            decision.reasons.insert(
+                1,
+                format!(
+                    "Pairs an SQL and a KV store, less {} for running a second store",
+                    self.rules.polyglot_penalties.extra_store
+                ),
+            );
+        }
+        decision
     }
 
-    fn check_constraints(&self, candidate: &Candidate, blueprint: &Blueprint) -> bool {
+    /// Decision for a `many` category, listing every member of the chosen
+    /// option.
+    fn build_many_decision(
+        &self,
+        topic: &str,
+        options: &[StackOption],
+        chosen_index: usize,
+        blueprint: &Blueprint,
+        mode: SearchMode,
+    ) -> Decision {
+        let chosen = &options[chosen_index];
+        let choices = chosen.names();
+
+        // Every provider appears in some option; list the best unchosen ones
+        let mut ranked: Vec<&ScoredCandidate> = Vec::new();
+        for member in options.iter().flat_map(|o| o.members.iter()) {
+            if !ranked
+                .iter()
+                .any(|r| r.candidate.name == member.candidate.name)
+            {
+                ranked.push(member);
+            }
+        }
+        ranked.sort_by(|a, b| b.score.total_cmp(&a.score));
+        let alternatives: Vec<String> = ranked
+            .iter()
+            .filter(|r| !choices.contains(&r.candidate.name))
+            .take(2)
+            .map(|r| r.candidate.name.clone())
+            .collect();
+
+        let mut reasons = vec!["Selected based on quality and cost balance".to_string()];
+        if choices.len() > 1 {
+            reasons.push(format!(
+                "Combines {} {topic} choices for redundancy",
+                choices.len()
+            ));
+        }
+        if let Some(prefs) = &blueprint.prefs {
+            for name in &choices {
+                if let Some(rank) = prefs.rank(topic, &[name]) {
+                    reasons.push(format!("{name} ranked #{} in team preferences", rank + 1));
+                }
+            }
+        }
+        if chosen_index > 0 {
+            match (mode, blueprint.constraints.monthly_cost_usd_max) {
+                (SearchMode::Pareto, _) => {
+                    reasons.push(format!(
+                        "The {topic} mix trades overall score on the Pareto frontier"
+                    ));
+                }
+                (SearchMode::Best | SearchMode::Truncated, Some(max_cost)) => {
+                    reasons.push(format!(
+                        "The {topic} mix is adjusted to keep the stack within ${max_cost}/month"
+                    ));
+                }
+                (SearchMode::Best | SearchMode::Truncated, None) => {}
+            }
+        }
+
+        Decision {
+            topic: topic.to_string(),
+            choice: choices.join(", "),
+            reasons,
+            alternatives,
+            score: chosen.members[0].score,
+            breakdown: self.explain_score(
+                &chosen.members[0].candidate.metrics,
+                chosen.members[0].score,
+                blueprint,
+            ),
+            rejected: if self.explain {
+                self.rejections(topic, blueprint, &[])
+            } else {
+                Vec::new()
+            },
+        }
+    }
+
+    /// Drop candidates that violate the blueprint constraints, logging why.
+    ///
+    /// When every candidate is rejected the error names the constraint that
+    /// eliminated each one.
+    fn filter_candidates(
+        &self,
+        category: &Category,
+        candidates: impl IntoIterator<Item = Candidate>,
+        blueprint: &Blueprint,
+        dependencies: &[(&str, Vec<String>)],
+    ) -> Result<Vec<Candidate>, RuneforgeError> {
+        let topic = category.name.as_str();
+        let mut rejections = Vec::new();
+        let kept: Vec<Candidate> = candidates
+            .into_iter()
+            .filter(
+                |c| match self.check_constraints(category, c, blueprint, dependencies) {
+                    Ok(()) => true,
+                    Err(rejection) => {
+                        observability::log_candidate_rejected(
+                            topic,
+                            &c.name,
+                            &rejection.to_string(),
+                        );
+                        rejections.push(rejection);
+                        false
+                    }
+                },
+            )
+            .collect();
+
+        if kept.is_empty() {
+            return Err(RuneforgeError::NoSuitableCandidates {
+                category: topic.to_string(),
+                rejections,
+            });
+        }
+
+        Ok(kept)
+    }
+
+    /// Every candidate for a topic that the constraint filters remove.
+    fn rejections(
+        &self,
+        topic: &str,
+        blueprint: &Blueprint,
+        dependencies: &[(&str, Vec<String>)],
+    ) -> Vec<Rejection> {
+        let Some(category) = self.categories().find(|c| c.name == topic) else {
+            return Vec::new();
+        };
+        self.rules
+            .candidates
+            .get(topic)
+            .iter()
+            .filter_map(|c| {
+                self.check_constraints(category, c, blueprint, dependencies)
+                    .err()
+            })
+            .collect()
+    }
+
+    /// Check a candidate against the blueprint constraints, returning the
+    /// filter that rejected it. `dependencies` are the names chosen for the
+    /// categories the candidate's category depends on.
+    fn check_constraints(
+        &self,
+        category: &Category,
+        candidate: &Candidate,
+        blueprint: &Blueprint,
+        dependencies: &[(&str, Vec<String>)],
+    ) -> Result<(), Rejection> {
+        let topic = category.name.as_str();
+        let reject = |filter: &str, required: String, actual: String| Rejection {
+            candidate: candidate.name.clone(),
+            filter: filter.to_string(),
+            required,
+            actual,
+        };
+
+        // Check single language mode
+        if category
+            .filters
+            .contains(&CategoryFilter::SingleLanguageMode)
+        {
+            if let Some(mode) = &blueprint.single_language_mode {
+                if candidate.name != mode.language() {
+                    return Err(reject(
+                        "single_language_mode",
+                        mode.language().to_string(),
+                        candidate.name.clone(),
+                    ));
+                }
+            }
+        }
+
+        // Check requirements on earlier choices
+        for (dependency, names) in dependencies {
+            let required = candidate.requires.as_ref().and_then(|r| r.get(dependency));
+            if let Some(required) = required {
+                if !names.iter().any(|n| n == required) {
+                    return Err(reject(
+                        &format!("requires.{dependency}"),
+                        required.to_string(),
+                        names.join(", "),
+                    ));
+                }
+            }
+        }
+
+        // Check persistence type
+        if category.filters.contains(&CategoryFilter::Persistence) {
+            if let Some(persistence) = &blueprint.constraints.persistence {
+                // `both` is also met by an SQL store paired with a KV store
+                let pairable = matches!(persistence, PersistenceType::Both)
+                    && matches!(candidate.persistence.as_deref(), Some("sql" | "kv"));
+                if candidate.persistence.as_deref() != Some(persistence.as_str()) && !pairable {
+                    let expected = match persistence {
+                        PersistenceType::Both => "both, sql or kv",
+                        other => other.as_str(),
+                    };
+                    return Err(reject(
+                        "persistence",
+                        expected.to_string(),
+                        candidate
+                            .persistence
+                            .clone()
+                            .unwrap_or_else(|| "unspecified".to_string()),
+                    ));
+                }
+            }
+        }
+
+        // Check avoid lists
+        if let Some(prefs) = &blueprint.prefs {
+            if prefs.avoids(topic, &[&candidate.name]) {
+                return Err(reject(
+                    "prefs.avoid",
+                    format!("not listed in prefs.avoid.{topic}"),
+                    "listed".to_string(),
+                ));
+            }
+        }
+
         // Check region constraints
         if let Some(allowed_regions) = &blueprint.constraints.region_allow {
+            let catalog = &self.rules.regions;
             let matches = candidate
                 .regions
                 .iter()
-                .any(|r| r == "*" || r == "global" || allowed_regions.contains(r));
+                .any(|r| allowed_regions.iter().any(|a| catalog.overlaps(r, a)));
             if !matches {
-                return false;
+                return Err(reject(
+                    "region_allow",
+                    format!("one of [{}]", allowed_regions.join(", ")),
+                    format!("[{}]", candidate.regions.join(", ")),
+                ));
             }
         }
 
         // Check cost constraints
         if let Some(max_cost) = blueprint.constraints.monthly_cost_usd_max {
-            let passed = candidate.monthly_cost_base <= max_cost;
-            observability::log_constraint_evaluation(
-                "monthly_cost",
-                max_cost,
-                candidate.monthly_cost_base,
-                passed,
-            );
+            let cost = self.candidate_cost(candidate, blueprint);
+            let passed = cost <= max_cost;
+            observability::log_constraint_evaluation("monthly_cost", max_cost, cost, passed);
             if !passed {
-                return false;
+                return Err(reject(
+                    "monthly_cost_usd_max",
+                    format!("<= ${max_cost}"),
+                    format!("${cost:.2}"),
+                ));
             }
         }
 
-        // Note: quality_min, security_min, and slo_min constraints could be added
-        // to the schema if needed. For now, these are checked via scoring.
+        // Check compliance features
+        for (compliance, requirement) in self.compliance_requirements(topic, blueprint) {
+            if let Some(missing) = requirement
+                .required_features
+                .iter()
+                .find(|f| !candidate.features.contains(f))
+            {
+                return Err(reject(
+                    &format!("compliance.{compliance}"),
+                    format!("feature {missing}"),
+                    format!("[{}]", candidate.features.join(", ")),
+                ));
+            }
+        }
+
+        // Check metric floors
+        let constraints = &blueprint.constraints;
+        let floors = [
+            (
+                "quality_min",
+                &constraints.quality_min,
+                candidate.metrics.quality,
+            ),
+            (
+                "security_min",
+                &constraints.security_min,
+                candidate.metrics.security,
+            ),
+            ("slo_min", &constraints.slo_min, candidate.metrics.slo),
+        ];
+        for (name, floor, actual) in floors {
+            let Some(min) = floor.as_ref().and_then(|f| f.for_category(topic)) else {
+                continue;
+            };
+            let passed = actual >= min;
+            observability::log_constraint_evaluation(name, min, actual, passed);
+            if !passed {
+                return Err(reject(name, format!(">= {min}"), actual.to_string()));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Monthly cost of a candidate at the blueprint's traffic: the flat base
+    /// plus its pricing curve, if any.
+    fn candidate_cost(&self, candidate: &Candidate, blueprint: &Blueprint) -> f64 {
+        let traffic = TrafficEstimate::from_profile(&blueprint.traffic_profile);
+        candidate.monthly_cost_base
+            + candidate
+                .pricing
+                .as_ref()
+                .map_or(0.0, |p| p.monthly_cost(&traffic))
+    }
+
+    /// Cost of the cheapest stack that passes every filter but the budget.
+    pub(crate) fn minimum_cost(&self, blueprint: &Blueprint) -> Result<f64, RuneforgeError> {
+        let mut unbounded = blueprint.clone();
+        unbounded.constraints.monthly_cost_usd_max = None;
+        let services = service_reserve(&self.rank_services(&unbounded)?, false);
+        Ok(self.rank_stack(&unbounded)?.cheapest() + services)
+    }
+
+    /// Every concrete region named by a candidate, sorted.
+    pub(crate) fn candidate_regions(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .categories()
+            .flat_map(|category| self.rules.candidates.get(&category.name))
+            .flat_map(|c| c.regions.iter())
+            .filter(|r| !regions::is_wildcard(r))
+            .cloned()
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
 
-        true
+    pub(crate) fn region_catalog(&self) -> &RegionCatalog {
+        &self.rules.regions
+    }
+
+    /// Regions every chosen candidate can serve, narrowed to the blueprint's
+    /// `region_allow`. `None` means every region.
+    fn target_regions(&self, blueprint: &Blueprint, chosen: &[&Candidate]) -> Option<Vec<String>> {
+        let catalog = &self.rules.regions;
+        let mut shared = blueprint.constraints.region_allow.clone();
+        for candidate in chosen {
+            if candidate.regions.iter().any(|r| regions::is_wildcard(r)) {
+                continue;
+            }
+            shared = catalog.intersect(shared.as_deref(), Some(&candidate.regions));
+        }
+        shared
+    }
+
+    /// Blueprint problems that do not stop selection, such as region names
+    /// missing from the rules' region catalog or preferences for categories
+    /// the rules do not have.
+    pub fn warnings(&self, blueprint: &Blueprint) -> Vec<String> {
+        let catalog = &self.rules.regions;
+        let mut warnings: Vec<String> = blueprint
+            .constraints
+            .region_allow
+            .iter()
+            .flatten()
+            .filter(|r| !catalog.is_empty() && !catalog.is_known(r))
+            .map(|r| format!("Unknown region '{r}' in region_allow; only exact matches apply"))
+            .collect();
+
+        if let Some(prefs) = &blueprint.prefs {
+            let kinds = self
+                .rules
+                .candidates
+                .services
+                .by_kind()
+                .map(|(kind, _)| kind);
+            let known = |key: &str| {
+                kinds.contains(&key) || self.rules.categories.iter().any(|c| c.name == key)
+            };
+            for (field, keys) in [("prefs", &prefs.ranked), ("prefs.avoid", &prefs.avoid)] {
+                warnings.extend(
+                    keys.keys()
+                        .filter(|key| !known(key))
+                        .map(|key| format!("Unknown category '{key}' in {field}; it is ignored")),
+                );
+            }
+        }
+        warnings
+    }
+
+    /// Compliance requirements from the rules that apply to a topic for the
+    /// blueprint's requested compliance types.
+    fn compliance_requirements(
+        &self,
+        topic: &str,
+        blueprint: &Blueprint,
+    ) -> Vec<(&'static str, &ComplianceRequirement)> {
+        let Some(compliance_types) = &blueprint.constraints.compliance else {
+            return Vec::new();
+        };
+
+        compliance_types
+            .iter()
+            .filter_map(|c| {
+                let key = c.as_str();
+                self.rules
+                    .compliance_requirements
+                    .get(key)
+                    .filter(|r| r.applies_to.is_empty() || r.applies_to.iter().any(|t| t == topic))
+                    .map(|r| (key, r))
+            })
+            .collect()
     }
 
     fn calculate_score(&self, metrics: &Metrics, blueprint: &Blueprint) -> f64 {
-        let weights = &self.rules.weights;
+        self.score_breakdown(metrics, blueprint).base_score()
+    }
+
+    /// Weighted metrics, adjustments and strategy behind a candidate's
+    /// score, before preferences.
+    fn score_breakdown(&self, metrics: &Metrics, blueprint: &Blueprint) -> ScoreBreakdown {
+        let metrics: Vec<MetricContribution> = self
+            .rules
+            .weighted_metrics(metrics, blueprint)
+            .into_iter()
+            .map(|(metric, value, weight)| MetricContribution {
+                metric: metric.to_string(),
+                value,
+                weight,
+                contribution: weight * value,
+            })
+            .collect();
+        let total_weight: f64 = metrics.iter().map(|m| m.weight).sum();
+
+        ScoreBreakdown {
+            strategy: self.rules.scoring.strategy,
+            priority: self.rules.scoring.priority.clone(),
+            metrics,
+            adjustments: self
+                .rules
+                .matching_adjustments(blueprint)
+                .map(|a| a.to_string())
+                .collect(),
+            normalization: match self.rules.weight_ceiling().max(total_weight) {
+                total if total > 0.0 => total,
+                _ => 1.0,
+            },
+            preference_bonus: 0.0,
+        }
+    }
 
-        let mut score = weights.quality * metrics.quality
-            + weights.slo * metrics.slo
-            + weights.cost * metrics.cost
-            + weights.security * metrics.security
-            + weights.ops * metrics.ops;
+    /// Breakdown for a chosen candidate when explaining, with the
+    /// preference bonus recovered from its final score.
+    fn explain_score(
+        &self,
+        metrics: &Metrics,
+        score: f64,
+        blueprint: &Blueprint,
+    ) -> Option<ScoreBreakdown> {
+        if !self.explain {
+            return None;
+        }
+        let mut breakdown = self.score_breakdown(metrics, blueprint);
+        breakdown.preference_bonus = score - breakdown.base_score();
+        Some(breakdown)
+    }
+
+    /// Raise a score towards 1 for candidates the team ranked under
+    /// `category`, so the bonus never pushes a score past 1.
+    fn apply_preference(
+        &self,
+        category: &str,
+        names: &[&str],
+        score: f64,
+        blueprint: &Blueprint,
+    ) -> f64 {
+        let bonus = blueprint
+            .prefs
+            .as_ref()
+            .map_or(0.0, |p| p.bonus(category, names));
+        score + (1.0 - score).max(0.0) * bonus
+    }
+
+    /// Rank the candidates of every service the blueprint declares, or of
+    /// the rules' default services that match it.
+    ///
+    /// A declared service that every candidate is filtered out of fails the
+    /// selection; a default service is only left out of the plan.
+    fn rank_services(&self, blueprint: &Blueprint) -> Result<Vec<ServiceSlot>, RuneforgeError> {
+        let specs: Vec<ServiceSpec> = match &blueprint.services {
+            Some(services) => services.clone(),
+            None => self
+                .rules
+                .default_services
+                .iter()
+                .filter(|d| d.when.matches(blueprint))
+                .map(DefaultService::spec)
+                .collect(),
+        };
+        let declared = blueprint.services.is_some();
+        let slots: Vec<ServiceSlot> = specs
+            .into_iter()
+            .map(|spec| self.generate_service_configurations(spec, declared, blueprint))
+            .collect();
+        if let Some(slot) = slots
+            .iter()
+            .find(|s| s.declared && s.configurations.is_empty())
+        {
+            return Err(RuneforgeError::NoSuitableCandidates {
+                category: slot.spec.name.clone(),
+                rejections: slot.rejections.clone(),
+            });
+        }
+        Ok(slots)
+    }
 
-        // Adjust for specific requirements
-        if blueprint.traffic_profile.latency_sensitive {
-            score += 0.1 * metrics.slo;
+    /// Choose services with a beam search over service combinations.
+    ///
+    /// Each combination is scored as the mean service score minus the
+    /// polyglot penalties; the primary language counts towards the language
+    /// total so services that match the backend are favoured. Combinations
+    /// that could not be completed within `budget` are dropped, and a
+    /// default service that no longer fits is left out.
+    fn select_services(
+        &self,
+        slots: &[ServiceSlot],
+        primary_language: &str,
+        budget: Option<f64>,
+    ) -> Vec<ServiceConfiguration> {
+        let slots: Vec<&ServiceSlot> = slots
+            .iter()
+            .filter(|s| !s.configurations.is_empty())
+            .collect();
+        let mut min_cost_tail = vec![0.0; slots.len() + 1];
+        for depth in (0..slots.len()).rev() {
+            let cheapest = if slots[depth].declared {
+                slots[depth].cheapest()
+            } else {
+                0.0
+            };
+            min_cost_tail[depth] = min_cost_tail[depth + 1] + cheapest;
         }
 
-        if blueprint.traffic_profile.global {
-            score += 0.05 * metrics.ops;
+        let mut beam = vec![ServiceCombination {
+            services: Vec::new(),
+            score: 0.0,
+            cost: 0.0,
+        }];
+
+        for (depth, slot) in slots.iter().enumerate() {
+            let mut expanded = Vec::with_capacity(beam.len() * slot.configurations.len());
+            for combination in &beam {
+                let before = expanded.len();
+                for option in &slot.configurations {
+                    let cost = combination.cost + option.cost;
+                    if let Some(budget) = budget {
+                        if cost + min_cost_tail[depth + 1] > budget + SEARCH_EPSILON {
+                            continue;
+                        }
+                    }
+                    let mut services = combination.services.clone();
+                    services.push(option.clone());
+                    let score = self.calculate_combination_score(&services, primary_language);
+                    expanded.push(ServiceCombination {
+                        services,
+                        score,
+                        cost,
+                    });
+                }
+                if expanded.len() == before && !slot.declared {
+                    expanded.push(combination.clone());
+                }
+            }
+
+            // Keep the best `beam` partial combinations
+            expanded.sort_by(|a, b| b.score.total_cmp(&a.score));
+            expanded.truncate(self.beam.max(1));
+            beam = expanded;
         }
 
-        // Normalize
-        score / 1.15
+        beam.into_iter()
+            .next()
+            .map(|c| c.services)
+            .unwrap_or_default()
     }
 
-    fn get_component_cost(&self, category: &str, name: &str) -> f64 {
-        let candidates = match category {
-            "backend" => &self.rules.candidates.backend,
-            "frontend" => &self.rules.candidates.frontend,
-            "database" => &self.rules.candidates.database,
-            "cache" => &self.rules.candidates.cache,
-            "queue" => &self.rules.candidates.queue,
-            "ai" => &self.rules.candidates.ai,
-            "infra" => &self.rules.candidates.infra,
-            "ci_cd" => &self.rules.candidates.ci_cd,
-            _ => return 0.0,
+    /// Filter, score and rank the candidates for one service.
+    fn generate_service_configurations(
+        &self,
+        spec: ServiceSpec,
+        declared: bool,
+        blueprint: &Blueprint,
+    ) -> ServiceSlot {
+        let kind = spec.kind.as_str();
+        let scoped = Blueprint {
+            constraints: spec.constraints.apply(&blueprint.constraints),
+            ..blueprint.clone()
         };
 
-        candidates
-            .iter()
-            .find(|c| c.name == name)
-            .map(|c| c.monthly_cost_base)
-            .unwrap_or(0.0)
+        let mut configurations = Vec::new();
+        let mut rejections = Vec::new();
+        for candidate in self.rules.candidates.services.get(spec.kind) {
+            // The base cost is paid for every instance
+            let mut view = candidate.as_candidate();
+            view.monthly_cost_base *= spec.count as f64;
+
+            if let Err(rejection) = self.check_service(&spec, candidate, &view, &scoped) {
+                observability::log_candidate_rejected(
+                    &spec.name,
+                    &candidate.name,
+                    &rejection.to_string(),
+                );
+                rejections.push(rejection);
+                continue;
+            }
+            configurations.push(ServiceConfiguration {
+                service: spec.name.clone(),
+                candidate: candidate.clone(),
+                score: self.apply_preference(
+                    kind,
+                    &[&candidate.name, &candidate.framework],
+                    self.calculate_score(&candidate.metrics, blueprint),
+                    blueprint,
+                ),
+                cost: self.candidate_cost(&view, blueprint),
+            });
+        }
+
+        // Sort by score descending
+        configurations.sort_by(|a, b| b.score.total_cmp(&a.score));
+        ServiceSlot {
+            spec,
+            configurations,
+            rejections,
+            declared,
+        }
     }
 
-    fn select_services(
+    /// Check a service candidate against the blueprint: its language
+    /// against `single_language_mode`, the service's workload, its framework
+    /// against the avoid lists, then the filters shared with stack
+    /// categories. `view` is the candidate as costed for every instance.
+    fn check_service(
         &self,
+        spec: &ServiceSpec,
+        candidate: &ServiceCandidate,
+        view: &Candidate,
         blueprint: &Blueprint,
-        _primary_language: &str,
-    ) -> Result<Vec<Service>, String> {
-        let mut services = Vec::new();
-
-        // Use beam search to find best service combinations
-        let beam_configurations =
-            self.generate_service_configurations(blueprint, _primary_language);
-
-        // Select API service
-        if let Some(api_service) =
-            self.select_best_service("api", &beam_configurations, blueprint)?
-        {
-            services.push(api_service);
+    ) -> Result<(), Rejection> {
+        let kind = spec.kind.as_str();
+        let reject = |filter: &str, required: String, actual: String| Rejection {
+            candidate: candidate.name.clone(),
+            filter: filter.to_string(),
+            required,
+            actual,
+        };
+        if let Some(mode) = &blueprint.single_language_mode {
+            if candidate.language != mode.language() {
+                return Err(reject(
+                    "single_language_mode",
+                    mode.language().to_string(),
+                    candidate.language.clone(),
+                ));
+            }
         }
-
-        // Select Edge service if needed for global traffic
-        if blueprint.traffic_profile.global {
-            if let Some(edge_service) =
-                self.select_best_service("edge", &beam_configurations, blueprint)?
-            {
-                services.push(edge_service);
+        if let Some(workload) = spec.workload {
+            if !candidate.workloads.is_empty() && !candidate.workloads.contains(&workload) {
+                let supported: Vec<&str> =
+                    candidate.workloads.iter().map(Workload::as_str).collect();
+                return Err(reject(
+                    "workload",
+                    workload.as_str().to_string(),
+                    format!("[{}]", supported.join(", ")),
+                ));
             }
         }
-
-        // Select Worker service if high RPS
-        if blueprint.traffic_profile.rps_peak > 10000.0 {
-            if let Some(worker_service) =
-                self.select_best_service("worker", &beam_configurations, blueprint)?
-            {
-                services.push(worker_service);
+        if let Some(prefs) = &blueprint.prefs {
+            if prefs.avoids(kind, &[&candidate.framework]) {
+                return Err(reject(
+                    "prefs.avoid",
+                    format!("not listed in prefs.avoid.{kind}"),
+                    format!("{} listed", candidate.framework),
+                ));
             }
         }
-
-        Ok(services)
+        self.check_constraints(&Category::new(kind), view, blueprint, &[])
     }
 
-    fn generate_service_configurations(
+    /// Decisions for a chosen service: `<name>.framework` and
+    /// `<name>.runtime`.
+    fn build_service_decisions(
         &self,
+        slot: &ServiceSlot,
+        chosen: &ServiceConfiguration,
+        primary_language: &str,
         blueprint: &Blueprint,
-        _primary_language: &str,
-    ) -> Vec<ServiceConfiguration> {
-        let mut configurations = Vec::new();
-
-        // Generate configurations based on beam width
-        let service_types = ["api", "edge", "worker"];
-
-        for service_type in &service_types {
-            let candidates = match *service_type {
-                "api" => &self.rules.candidates.services.api,
-                "edge" => &self.rules.candidates.services.edge,
-                "worker" => &self.rules.candidates.services.worker,
-                _ => continue,
-            };
-
-            // Score and rank candidates
-            let mut scored_candidates: Vec<(ServiceCandidate, f64)> = candidates
+    ) -> [Decision; 2] {
+        let (name, kind) = (&slot.spec.name, slot.spec.kind.as_str());
+        let candidate = &chosen.candidate;
+        let others = || {
+            slot.configurations
                 .iter()
-                .filter(|c| {
-                    // Filter by single language mode if specified
-                    if let Some(lang_mode) = &blueprint.single_language_mode {
-                        match lang_mode {
-                            LanguageMode::Rust => c.language == "Rust",
-                            LanguageMode::Go => c.language == "Go",
-                            LanguageMode::Ts => c.language == "TypeScript",
-                        }
-                    } else {
-                        true // No language mode restriction
-                    }
-                })
-                .map(|c| {
-                    let score = self.calculate_service_score(c, blueprint);
-                    (c.clone(), score)
-                })
-                .collect();
-
-            // Sort by score descending
-            scored_candidates.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
-
-            // Take top beam candidates
-            let top_candidates: Vec<ServiceConfiguration> = scored_candidates
-                .into_iter()
-                .take(self.beam)
-                .map(|(candidate, score)| ServiceConfiguration {
-                    service_type: service_type.to_string(),
-                    candidate,
-                    score,
-                })
-                .collect();
+                .filter(|c| c.candidate.name != candidate.name)
+        };
+        let alternatives = |field: fn(&ServiceCandidate) -> &String| {
+            let mut names: Vec<String> = Vec::new();
+            for other in others() {
+                let name = field(&other.candidate);
+                if name != field(candidate) && !names.contains(name) {
+                    names.push(name.clone());
+                }
+            }
+            names.truncate(3);
+            names
+        };
 
-            configurations.extend(top_candidates);
+        let mut reasons = Vec::new();
+        if candidate.language == primary_language {
+            reasons.push(format!("Shares the {primary_language} primary language"));
+        } else {
+            reasons.push(format!(
+                "Adds {} for the {name} service",
+                candidate.language
+            ));
         }
-
-        configurations
-    }
-
-    fn select_best_service(
-        &self,
-        service_type: &str,
-        configurations: &[ServiceConfiguration],
-        _blueprint: &Blueprint,
-    ) -> Result<Option<Service>, String> {
-        use crate::schema::Service;
-
-        let relevant_configs: Vec<&ServiceConfiguration> = configurations
-            .iter()
-            .filter(|c| c.service_type == service_type)
-            .collect();
-
-        if relevant_configs.is_empty() {
-            return Ok(None);
+        if chosen.score > 0.8 {
+            reasons.push("High overall score across all metrics".to_string());
+        }
+        if let Some(rank) = blueprint
+            .prefs
+            .as_ref()
+            .and_then(|p| p.rank(kind, &[&candidate.name, &candidate.framework]))
+        {
+            reasons.push(format!("Ranked #{} in team preferences", rank + 1));
+        }
+        if blueprint.traffic_profile.latency_sensitive && candidate.metrics.slo > 0.85 {
+            reasons.push("Excellent performance for latency-sensitive workload".to_string());
+        }
+        let top = &slot.configurations[0];
+        if top.candidate.name != candidate.name {
+            match blueprint.constraints.monthly_cost_usd_max {
+                Some(max_cost) if top.cost > chosen.cost => reasons.push(format!(
+                    "Preferred over {} to keep the stack within ${max_cost}/month",
+                    top.candidate.framework
+                )),
+                _ => reasons.push(format!(
+                    "Preferred over {} to share languages and toolchains",
+                    top.candidate.framework
+                )),
+            }
         }
 
-        // Select best based on score
-        let best = relevant_configs
-            .into_iter()
-            .max_by(|a, b| a.score.partial_cmp(&b.score).unwrap())
-            .unwrap();
-
-        Ok(Some(Service {
-            name: service_type.to_string(),
-            kind: service_type.to_string(),
-            language: best.candidate.language.clone(),
-            framework: best.candidate.framework.clone(),
-            runtime: best.candidate.runtime.clone(),
-            build: best.candidate.build.clone(),
-            tests: best.candidate.tests.clone(),
-        }))
+        let breakdown = self.explain_score(&candidate.metrics, chosen.score, blueprint);
+        [
+            Decision {
+                topic: format!("{name}.framework"),
+                choice: candidate.framework.clone(),
+                reasons,
+                alternatives: alternatives(|c| &c.framework),
+                score: chosen.score,
+                breakdown: breakdown.clone(),
+                rejected: if self.explain {
+                    slot.rejections.clone()
+                } else {
+                    Vec::new()
+                },
+            },
+            Decision {
+                topic: format!("{name}.runtime"),
+                choice: candidate.runtime.clone(),
+                reasons: vec![format!("Runs {}", candidate.framework)],
+                alternatives: alternatives(|c| &c.runtime),
+                score: chosen.score,
+                breakdown,
+                rejected: Vec::new(),
+            },
+        ]
     }
 
-    fn calculate_service_score(&self, candidate: &ServiceCandidate, blueprint: &Blueprint) -> f64 {
-        let weights = &self.rules.weights;
-        let metrics = &candidate.metrics;
-
-        // Base score calculation
-        let mut score = weights.quality * metrics.quality
-            + weights.slo * metrics.slo
-            + weights.cost * metrics.cost
-            + weights.security * metrics.security
-            + weights.ops * metrics.ops;
-
-        // Adjust for latency sensitivity
-        if blueprint.traffic_profile.latency_sensitive {
-            score = score * 0.8 + metrics.slo * 0.2;
-        }
-
-        // Penalty for cost if over budget
-        if let Some(max_cost) = blueprint.constraints.monthly_cost_usd_max {
-            if candidate.monthly_cost_base > max_cost * 0.1 {
-                score *= 0.8;
+    /// Mean service score minus the language and toolchain penalties.
+    fn calculate_combination_score(
+        &self,
+        services: &[ServiceConfiguration],
+        primary_language: &str,
+    ) -> f64 {
+        let penalties = &self.rules.polyglot_penalties;
+
+        let mut languages = vec![primary_language];
+        let mut toolchains: Vec<&str> = Vec::new();
+        for config in services {
+            if !languages.contains(&config.candidate.language.as_str()) {
+                languages.push(&config.candidate.language);
+            }
+            let toolchain = config.candidate.toolchain();
+            if !toolchains.contains(&toolchain) {
+                toolchains.push(toolchain);
             }
         }
 
-        score
+        let mean = services.iter().map(|c| c.score).sum::<f64>() / services.len() as f64;
+        mean - penalties.extra_language * (languages.len() - 1) as f64
+            - penalties.extra_toolchain * toolchains.len().saturating_sub(1) as f64
     }
 }
 
@@ -766,6 +3032,11 @@ weights:
   cost: 0.20
   security: 0.15
   ops: 0.10
+regions:
+  us: { geography: "United States" }
+  eu: { geography: "Europe" }
+  us-east-1: { provider: aws, parent: us }
+  eu-west-1: { provider: aws, parent: eu }
 candidates:
   language:
     - name: "Rust"
@@ -815,6 +3086,7 @@ candidates:
       persistence: "sql"
       metrics: { quality: 0.9, slo: 0.85, cost: 0.7, security: 0.9, ops: 0.8 }
       regions: ["*"]
+      features: ["encryption", "audit_log", "access_control"]
       monthly_cost_base: 200
     - name: "Redis"
       persistence: "kv"
@@ -825,6 +3097,7 @@ candidates:
       persistence: "both"
       metrics: { quality: 0.85, slo: 0.9, cost: 0.8, security: 0.85, ops: 0.9 }
       regions: ["*"]
+      features: ["encryption", "audit_log", "access_control"]
       monthly_cost_base: 180
   cache:
     - name: "Redis"
@@ -870,6 +3143,7 @@ candidates:
     - name: "GitHub Actions"
       metrics: { quality: 0.85, slo: 0.8, cost: 0.9, security: 0.85, ops: 0.9 }
       regions: ["*"]
+      features: ["audit_log", "version_control", "change_management"]
       monthly_cost_base: 20
     - name: "GitLab CI"
       metrics: { quality: 0.8, slo: 0.75, cost: 0.85, security: 0.8, ops: 0.85 }
@@ -878,11 +3152,50 @@ candidates:
 compliance_requirements:
   hipaa:
     required_features: ["encryption", "audit_log", "access_control"]
+    applies_to: ["database"]
   sox:
     required_features: ["audit_log", "version_control", "change_management"]
+    applies_to: ["ci_cd"]
 "#
     }
 
+    /// Test rules plus a Rust and a Go API service and a Go-only edge service.
+    fn get_test_rules_with_services() -> String {
+        let services = r#"candidates:
+  services:
+    api:
+      - name: "Rust API"
+        language: "Rust"
+        framework: "Axum"
+        runtime: "rust@1.82"
+        build: "cargo build"
+        tests: "cargo test"
+        metrics: { quality: 0.8, slo: 0.8, cost: 0.8, security: 0.8, ops: 0.8 }
+        regions: ["*"]
+        monthly_cost_base: 10
+      - name: "Go API"
+        language: "Go"
+        framework: "Gin"
+        runtime: "go@1.22"
+        build: "go build"
+        tests: "go test ./..."
+        metrics: { quality: 0.84, slo: 0.84, cost: 0.84, security: 0.84, ops: 0.84 }
+        regions: ["*"]
+        monthly_cost_base: 10
+    edge:
+      - name: "Go Edge"
+        language: "Go"
+        framework: "Native"
+        runtime: "go@1.22"
+        build: "go build"
+        tests: "go test ./..."
+        metrics: { quality: 0.8, slo: 0.8, cost: 0.8, security: 0.8, ops: 0.8 }
+        regions: ["*"]
+        monthly_cost_base: 10
+"#;
+        get_test_rules().replacen("candidates:\n", services, 1)
+    }
+
     fn get_test_blueprint() -> Blueprint {
         Blueprint {
             project_name: "test-project".to_string(),
@@ -892,14 +3205,21 @@ compliance_requirements:
                 persistence: None,
                 region_allow: None,
                 compliance: None,
+                quality_min: None,
+                security_min: None,
+                slo_min: None,
             },
             traffic_profile: TrafficProfile {
                 rps_peak: 1000.0,
                 global: true,
                 latency_sensitive: false,
+                avg_payload_kb: None,
+                read_ratio: None,
             },
             prefs: None,
             single_language_mode: None,
+            services: None,
+            categories: BTreeMap::new(),
         }
     }
 
@@ -910,130 +3230,787 @@ compliance_requirements:
     }
 
     #[test]
-    fn test_selector_invalid_yaml() {
-        let invalid_yaml = "invalid: yaml: content:";
-        let selector = Selector::new(invalid_yaml, 42, 8);
-        assert!(selector.is_err());
-        assert!(selector.unwrap_err().contains("Failed to parse rules"));
+    fn test_non_finite_numbers_are_rejected() {
+        let rules = get_test_rules_with_services();
+        let cases = [
+            (
+                "candidates:\n  database:\n    - { name: PostgreSQL, metrics: { quality: .nan } }",
+                "database candidate 'PostgreSQL' metric quality must be a finite number, not NaN",
+            ),
+            (
+                "candidates:\n  services:\n    api:\n      - { name: Go API, monthly_cost_base: .inf }",
+                "api candidate 'Go API' monthly_cost_base must be a finite number, not inf",
+            ),
+            (
+                "dimensions:\n  - { name: dx, weight: .nan, default: 0.5 }",
+                "dimensions.dx.weight must be a finite number, not NaN",
+            ),
+            (
+                "candidates:\n  infra:\n    - { name: Terraform, pricing: { instance_monthly: -70 } }",
+                "infra candidate 'Terraform' pricing.instance_monthly must be a non-negative number, not -70",
+            ),
+        ];
+        for (overlay, message) in cases {
+            let err = Selector::from_layers(&[&rules, overlay], 42, 8).unwrap_err();
+            assert_eq!(err.to_string(), format!("Failed to parse rules: {message}"));
+        }
+    }
+
+    #[test]
+    fn test_selector_invalid_yaml() {
+        let invalid_yaml = "invalid: yaml: content:";
+        let selector = Selector::new(invalid_yaml, 42, 8);
+        assert!(selector.is_err());
+        assert!(matches!(
+            selector.unwrap_err(),
+            RuneforgeError::Rules { .. }
+        ));
+    }
+
+    #[test]
+    fn test_select_complete_stack() {
+        let selector = Selector::new(get_test_rules(), 42, 8).unwrap();
+        let blueprint = get_test_blueprint();
+
+        let result = selector.select(&blueprint);
+        assert!(result.is_ok());
+
+        let plan = result.unwrap();
+        assert!(!plan.stack.language.is_empty());
+        for category in [
+            "frontend", "backend", "database", "cache", "queue", "infra", "ci_cd",
+        ] {
+            assert!(plan.stack.choice(category).is_some_and(|c| !c.is_empty()));
+        }
+        assert!(!plan.stack.choices("ai").is_empty());
+
+        // Check meta information
+        assert_eq!(plan.meta.seed, 42);
+        assert!(plan.meta.blueprint_hash.starts_with("sha256:"));
+        assert!(plan.meta.plan_hash.starts_with("sha256:"));
+    }
+
+    #[test]
+    fn test_single_language_mode() {
+        let selector = Selector::new(get_test_rules(), 42, 8).unwrap();
+
+        // Test Rust mode
+        let mut blueprint = get_test_blueprint();
+        blueprint.single_language_mode = Some(LanguageMode::Rust);
+
+        let result = selector.select(&blueprint);
+        assert!(result.is_ok());
+        let plan = result.unwrap();
+        assert_eq!(plan.stack.language, "Rust");
+        assert!(matches!(
+            plan.stack.choice("backend"),
+            Some("Actix Web" | "Axum")
+        ));
+
+        // Test Go mode
+        blueprint.single_language_mode = Some(LanguageMode::Go);
+        let result = selector.select(&blueprint);
+        assert!(result.is_ok());
+        let plan = result.unwrap();
+        assert_eq!(plan.stack.language, "Go");
+        assert_eq!(plan.stack.choice("backend"), Some("Gin"));
+
+        // Test TypeScript mode
+        blueprint.single_language_mode = Some(LanguageMode::Ts);
+        let result = selector.select(&blueprint);
+        assert!(result.is_ok());
+        let plan = result.unwrap();
+        assert_eq!(plan.stack.language, "TypeScript");
+        assert_eq!(plan.stack.choice("backend"), Some("Express"));
+    }
+
+    #[test]
+    fn test_persistence_constraints() {
+        let selector = Selector::new(get_test_rules(), 42, 8).unwrap();
+        let mut blueprint = get_test_blueprint();
+
+        // Test SQL constraint
+        blueprint.constraints.persistence = Some(PersistenceType::Sql);
+        let result = selector.select(&blueprint);
+        assert!(result.is_ok());
+        let plan = result.unwrap();
+        assert_eq!(plan.stack.choice("database"), Some("PostgreSQL"));
+
+        // Test KV constraint
+        blueprint.constraints.persistence = Some(PersistenceType::Kv);
+        let result = selector.select(&blueprint);
+        assert!(result.is_ok());
+        let plan = result.unwrap();
+        assert_eq!(plan.stack.choice("database"), Some("Redis"));
+
+        // Test Both constraint
+        blueprint.constraints.persistence = Some(PersistenceType::Both);
+        let result = selector.select(&blueprint);
+        assert!(result.is_ok());
+        let plan = result.unwrap();
+        assert_eq!(plan.stack.choice("database"), Some("DynamoDB"));
+        let roles = plan.stack.data_roles.unwrap();
+        assert_eq!(roles["sql"], "DynamoDB");
+        assert_eq!(roles["kv"], "DynamoDB");
+    }
+
+    #[test]
+    fn test_persistence_both_pairs_stores() {
+        let rules = get_test_rules();
+        let mut blueprint = get_test_blueprint();
+        blueprint.constraints.persistence = Some(PersistenceType::Both);
+
+        // Without a multi-model store, an SQL and a KV store share the roles
+        let overlay = "candidates:\n  database:\n    - { name: DynamoDB, remove: true }";
+        let selector = Selector::from_layers(&[rules, overlay], 42, 8).unwrap();
+        let plan = selector.select(&blueprint).unwrap();
+        assert_eq!(plan.stack.choices("database"), ["PostgreSQL", "Redis"]);
+        let roles = plan.stack.data_roles.as_ref().unwrap();
+        assert_eq!(roles["sql"], "PostgreSQL");
+        assert_eq!(roles["kv"], "Redis");
+        let decision = plan
+            .decisions
+            .iter()
+            .find(|d| d.topic == "database")
+            .unwrap();
+        assert_eq!(decision.choice, "PostgreSQL, Redis");
+        assert_eq!(
+            decision.reasons[0],
+            "PostgreSQL serves sql data; Redis serves kv data"
+        );
+        assert!(decision.reasons[1].contains("less 0.05"));
+
+        // The pair competes with the multi-model store on score and cost
+        let database = |plan: &StackPlan| plan.stack.choices("database").join(" + ");
+        let pairs = |penalty: f64| {
+            let overlay = format!("polyglot_penalties: {{ extra_language: 0.05, extra_toolchain: 0.02, extra_store: {penalty} }}");
+            Selector::from_layers(&[rules, &overlay], 42, 8).unwrap()
+        };
+        let multi_model = pairs(0.05).select(&blueprint).unwrap();
+        assert_eq!(database(&multi_model), "DynamoDB");
+        let paired = pairs(-0.2).select(&blueprint).unwrap();
+        assert_eq!(database(&paired), "PostgreSQL + Redis");
+        assert!(paired.estimated.monthly_cost_usd > multi_model.estimated.monthly_cost_usd);
+        // Pareto plans score the pair the same way
+        for (penalty, plan) in [(0.05, &multi_model), (-0.2, &paired)] {
+            let frontier = pairs(penalty).select_pareto(&blueprint, None).unwrap();
+            assert_eq!(database(&frontier[0]), database(plan));
+        }
+        blueprint.constraints.monthly_cost_usd_max = Some(multi_model.estimated.monthly_cost_usd);
+        let within_budget = pairs(-0.2).select(&blueprint).unwrap();
+        assert_eq!(database(&within_budget), "DynamoDB");
+        blueprint.constraints.monthly_cost_usd_max = None;
+
+        // Pair scores stay within [0, 1] whatever the penalty
+        let overlay = "candidates:\n  database:\n    - { name: DynamoDB, remove: true }";
+        for (penalty, score) in [(2.0, 0.0), (-2.0, 1.0)] {
+            let penalties = format!("polyglot_penalties: {{ extra_language: 0.05, extra_toolchain: 0.02, extra_store: {penalty} }}");
+            let selector = Selector::from_layers(&[rules, overlay, &penalties], 42, 8).unwrap();
+            let plan = selector.select(&blueprint).unwrap();
+            let decision = plan
+                .decisions
+                .iter()
+                .find(|d| d.topic == "database")
+                .unwrap();
+            assert_eq!(decision.score, score);
+            crate::schema::validate_stack_plan(&plan).unwrap();
+        }
+
+        // A store of one role has nothing to pair with
+        let overlay = "candidates:\n  database:\n    - { name: DynamoDB, remove: true }\n    - { name: Redis, remove: true }";
+        let selector = Selector::from_layers(&[rules, overlay], 42, 8).unwrap();
+        match selector.select(&blueprint).unwrap_err() {
+            RuneforgeError::NoSuitableCandidates {
+                category,
+                rejections,
+            } => {
+                assert_eq!(category, "database");
+                assert_eq!(rejections[0].candidate, "PostgreSQL");
+                assert_eq!(rejections[0].required, "a kv store to pair with");
+            }
+            other => panic!("Expected NoSuitableCandidates, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_region_constraints() {
+        let selector = Selector::new(get_test_rules(), 42, 8).unwrap();
+        let mut blueprint = get_test_blueprint();
+
+        // Constrain to us-east-1 only
+        blueprint.constraints.region_allow = Some(vec!["us-east-1".to_string()]);
+
+        let result = selector.select(&blueprint);
+        assert!(result.is_ok());
+
+        // All selected components should support us-east-1 or be global
+        let plan = result.unwrap();
+
+        // Frontend should be either SvelteKit (global) or Next.js (supports us-east-1)
+        assert!(matches!(
+            plan.stack.choice("frontend"),
+            Some("SvelteKit" | "Next.js")
+        ));
+    }
+
+    #[test]
+    fn test_region_catalog_matching() {
+        let selector = Selector::new(get_test_rules(), 42, 8).unwrap();
+        let mut blueprint = get_test_blueprint();
+        let mut prefs = Preferences::default();
+        prefs
+            .ranked
+            .insert("frontend".to_string(), vec!["Next.js".to_string()]);
+        prefs.strength = Some(1.0);
+        blueprint.prefs = Some(prefs);
+
+        // `us` covers Next.js's us-east-1 deployment
+        blueprint.constraints.region_allow = Some(vec!["us".to_string()]);
+        let plan = selector.select(&blueprint).unwrap();
+        assert_eq!(plan.stack.choice("frontend"), Some("Next.js"));
+        assert_eq!(
+            plan.estimated.notes.unwrap()[0],
+            "Target regions: us-east-1"
+        );
+        assert!(selector.warnings(&blueprint).is_empty());
+
+        // Without a shared region Next.js is rejected
+        blueprint.constraints.region_allow = Some(vec!["ap-south-1".to_string()]);
+        let plan = selector.select(&blueprint).unwrap();
+        assert_eq!(plan.stack.choice("frontend"), Some("SvelteKit"));
+        assert_eq!(
+            selector.warnings(&blueprint),
+            vec![
+                "Unknown region 'ap-south-1' in region_allow; only exact matches apply".to_string()
+            ]
+        );
+
+        // Unconstrained plans target the regions every component shares
+        blueprint.constraints.region_allow = None;
+        let plan = selector.select(&blueprint).unwrap();
+        assert_eq!(
+            plan.estimated.notes.unwrap()[0],
+            "Target regions: us-east-1, eu-west-1"
+        );
+    }
+
+    #[test]
+    fn test_unknown_preference_categories_warn() {
+        let selector = Selector::new(&get_test_rules_with_services(), 42, 8).unwrap();
+        let mut blueprint = get_test_blueprint();
+        let mut prefs = Preferences::default();
+        for key in ["databse", "api"] {
+            prefs
+                .ranked
+                .insert(key.to_string(), vec!["PostgreSQL".to_string()]);
+        }
+        prefs
+            .avoid
+            .insert("queue".to_string(), vec!["Kafka".to_string()]);
+        prefs
+            .avoid
+            .insert("ques".to_string(), vec!["Kafka".to_string()]);
+        blueprint.prefs = Some(prefs);
+
+        assert_eq!(
+            selector.warnings(&blueprint),
+            vec![
+                "Unknown category 'databse' in prefs; it is ignored".to_string(),
+                "Unknown category 'ques' in prefs.avoid; it is ignored".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_incompatible_choices_without_budget() {
+        // Each category has a candidate for some language, but not the same one
+        let overlay = r#"
+categories:
+  - name: auth
+    depends_on: [language]
+  - name: search
+    depends_on: [language]
+candidates:
+  auth:
+    - name: "Rust Auth"
+      requires: { language: "Rust" }
+      metrics: { quality: 0.8, slo: 0.8, cost: 0.8, security: 0.9, ops: 0.8 }
+      regions: ["*"]
+  search:
+    - name: "Go Search"
+      requires: { language: "Go" }
+      metrics: { quality: 0.8, slo: 0.8, cost: 0.8, security: 0.9, ops: 0.8 }
+      regions: ["*"]
+"#;
+        let rules = include_str!("../resources/rules.yaml");
+        let selector = Selector::from_layers(&[rules, overlay], 42, 8).unwrap();
+        let mut blueprint = get_test_blueprint();
+        blueprint.constraints.monthly_cost_usd_max = None;
+
+        for err in [
+            selector.select(&blueprint).unwrap_err(),
+            selector.select_pareto(&blueprint, None).unwrap_err(),
+        ] {
+            assert!(matches!(err, RuneforgeError::NoCompatibleStack));
+        }
+    }
+
+    #[test]
+    fn test_cost_constraints() {
+        let selector = Selector::new(get_test_rules(), 42, 8).unwrap();
+        let mut blueprint = get_test_blueprint();
+
+        // Set very low cost constraint
+        blueprint.constraints.monthly_cost_usd_max = Some(100.0);
+
+        let result = selector.select(&blueprint);
+        assert!(result.is_err());
+        let err_msg = result.unwrap_err().to_string();
+        // Check that it's a cost constraint error
+        assert!(
+            err_msg.contains("cost constraint") || err_msg.contains("No suitable"),
+            "Expected cost constraint error, got: {err_msg}"
+        );
+    }
+
+    #[test]
+    fn test_tight_budget_searches_combinations() {
+        let selector = Selector::new(get_test_rules(), 42, 8).unwrap();
+        let mut blueprint = get_test_blueprint();
+
+        // The per-category favourites cost $875; a slightly weaker mix fits $800
+        let unconstrained = selector.select(&blueprint).unwrap();
+        assert_eq!(unconstrained.estimated.monthly_cost_usd, 875.0);
+
+        blueprint.constraints.monthly_cost_usd_max = Some(800.0);
+        let plan = selector.select(&blueprint).unwrap();
+        assert!(plan.estimated.monthly_cost_usd <= 800.0);
+
+        // Swapping OpenAI for RuneSage is the cheapest loss in total score
+        assert_eq!(plan.stack.choices("ai"), vec!["Claude", "RuneSage"]);
+        assert_eq!(
+            plan.stack.choice("database"),
+            unconstrained.stack.choice("database")
+        );
+        assert_eq!(
+            plan.stack.choice("queue"),
+            unconstrained.stack.choice("queue")
+        );
+    }
+
+    #[test]
+    fn test_budget_leaves_room_for_services() {
+        let selector = Selector::new(&get_test_rules_with_services(), 42, 8).unwrap();
+        let mut blueprint = get_test_blueprint();
+
+        // The favourite components alone use up the whole budget
+        let components = Selector::new(get_test_rules(), 42, 8)
+            .unwrap()
+            .select(&blueprint)
+            .unwrap()
+            .estimated
+            .monthly_cost_usd;
+        blueprint.constraints.monthly_cost_usd_max = Some(components);
+        let plan = selector.select(&blueprint).unwrap();
+        assert!(!plan.stack.services.as_ref().unwrap().is_empty());
+        assert!(plan.estimated.monthly_cost_usd <= components);
+
+        // Default services are left out when no stack leaves room for them
+        let cheapest = selector.minimum_cost(&blueprint).unwrap();
+        blueprint.constraints.monthly_cost_usd_max = Some(cheapest);
+        let plan = selector.select(&blueprint).unwrap();
+        assert!(plan.stack.services.as_ref().unwrap().is_empty());
+        assert!(plan
+            .estimated
+            .notes
+            .unwrap()
+            .iter()
+            .any(|n| n.starts_with("No api service: its cheapest candidate ($10.00)")));
+    }
+
+    #[test]
+    fn test_search_stops_at_node_limit() {
+        let selector = Selector::new(get_test_rules(), 42, 8).unwrap();
+        let mut blueprint = get_test_blueprint();
+        blueprint.constraints.monthly_cost_usd_max = Some(800.0);
+        let ranked = selector.rank_stack(&blueprint).unwrap();
+
+        let mut search = StackSearch::new(Some(800.0));
+        search.run(&ranked);
+        assert!(!search.truncated);
+        let visited = search.nodes;
+
+        let mut bounded = StackSearch {
+            limit: visited / 2,
+            ..StackSearch::new(Some(800.0))
+        };
+        bounded.run(&ranked);
+        assert!(bounded.truncated);
+        assert_eq!(bounded.nodes, visited / 2);
     }
 
     #[test]
-    fn test_select_complete_stack() {
+    fn test_pareto_plans_are_non_dominated() {
         let selector = Selector::new(get_test_rules(), 42, 8).unwrap();
-        let blueprint = get_test_blueprint();
+        let mut blueprint = get_test_blueprint();
+        blueprint.constraints.monthly_cost_usd_max = Some(800.0);
 
-        let result = selector.select(&blueprint);
-        assert!(result.is_ok());
+        let plans = selector.select_pareto(&blueprint, None).unwrap();
+        assert!(plans.len() > 1);
 
-        let plan = result.unwrap();
-        assert!(!plan.stack.language.is_empty());
-        assert!(!plan.stack.frontend.is_empty());
-        assert!(!plan.stack.backend.is_empty());
-        assert!(!plan.stack.database.is_empty());
-        assert!(!plan.stack.cache.is_empty());
-        assert!(!plan.stack.queue.is_empty());
-        assert!(!plan.stack.ai.is_empty());
-        assert!(!plan.stack.infra.is_empty());
-        assert!(!plan.stack.ci_cd.is_empty());
+        // The best single plan is on the frontier and comes first
+        let best = selector.select(&blueprint).unwrap();
+        assert_eq!(plans[0].stack.choices("ai"), best.stack.choices("ai"));
+        assert_eq!(
+            plans[0].stack.choice("database"),
+            best.stack.choice("database")
+        );
 
-        // Check meta information
-        assert_eq!(plan.meta.seed, 42);
-        assert!(plan.meta.blueprint_hash.starts_with("sha256:"));
-        assert!(plan.meta.plan_hash.starts_with("sha256:"));
+        let costs: Vec<f64> = plans.iter().map(|p| p.estimated.monthly_cost_usd).collect();
+        assert!(costs.iter().all(|c| *c <= 800.0));
+        // A cheaper plan than the best one must be part of the trade-off curve
+        assert!(costs.iter().any(|c| *c < best.estimated.monthly_cost_usd));
+
+        // No two plans share the same stack
+        for (i, a) in plans.iter().enumerate() {
+            for b in plans.iter().skip(i + 1) {
+                let a_json = serde_json::to_string(&a.stack).unwrap();
+                let b_json = serde_json::to_string(&b.stack).unwrap();
+                assert_ne!(a_json, b_json);
+            }
+        }
+
+        // A limit keeps both ends of the trade-off curve
+        let limited = selector.select_pareto(&blueprint, Some(3)).unwrap();
+        assert_eq!(limited.len(), 3);
+        let stack = |plan: &StackPlan| serde_json::to_string(&plan.stack).unwrap();
+        assert_eq!(stack(&limited[0]), stack(&plans[0]));
+        assert_eq!(stack(&limited[2]), stack(plans.last().unwrap()));
+        assert!(limited
+            .iter()
+            .all(|l| plans.iter().any(|p| stack(p) == stack(l))));
     }
 
     #[test]
-    fn test_single_language_mode() {
-        let selector = Selector::new(get_test_rules(), 42, 8).unwrap();
-
-        // Test Rust mode
-        let mut blueprint = get_test_blueprint();
-        blueprint.single_language_mode = Some(LanguageMode::Rust);
+    fn test_service_beam_search_with_polyglot_penalty() {
+        // The Go API scores higher alone, but only beats the Rust API once the
+        // Go-only edge service is taken into account
+        let rules = get_test_rules_with_services();
+        let blueprint = get_test_blueprint();
 
-        let result = selector.select(&blueprint);
-        assert!(result.is_ok());
-        let plan = result.unwrap();
+        let greedy = Selector::new(&rules, 42, 1).unwrap();
+        let plan = greedy.select(&blueprint).unwrap();
         assert_eq!(plan.stack.language, "Rust");
-        assert!(plan.stack.backend == "Actix Web" || plan.stack.backend == "Axum");
+        let services = plan.stack.services.unwrap();
+        assert_eq!(services[0].language, "Rust");
+        assert_eq!(services[1].language, "Go");
+
+        let wide = Selector::new(&rules, 42, 8).unwrap();
+        let plan = wide.select(&blueprint).unwrap();
+        let services = plan.stack.services.unwrap();
+        assert_eq!(services[0].language, "Go");
+        assert_eq!(services[1].language, "Go");
+    }
 
-        // Test Go mode
-        blueprint.single_language_mode = Some(LanguageMode::Go);
-        let result = selector.select(&blueprint);
-        assert!(result.is_ok());
-        let plan = result.unwrap();
-        assert_eq!(plan.stack.language, "Go");
-        assert_eq!(plan.stack.backend, "Gin");
+    #[test]
+    fn test_service_decisions() {
+        let rules = get_test_rules_with_services();
+        let selector = Selector::new(&rules, 42, 8).unwrap().with_explain(true);
+        let blueprint = get_test_blueprint();
 
-        // Test TypeScript mode
-        blueprint.single_language_mode = Some(LanguageMode::Ts);
-        let result = selector.select(&blueprint);
-        assert!(result.is_ok());
-        let plan = result.unwrap();
-        assert_eq!(plan.stack.language, "TypeScript");
-        assert_eq!(plan.stack.backend, "Express");
+        let plan = selector.select(&blueprint).unwrap();
+        let decision = |topic: &str| plan.decisions.iter().find(|d| d.topic == topic).unwrap();
+        let api = decision("api.framework");
+        assert_eq!(api.choice, "Gin");
+        assert_eq!(api.alternatives, vec!["Axum"]);
+        assert!(api.score > 0.0);
+        assert!(api.breakdown.is_some());
+        assert_eq!(decision("api.runtime").choice, "go@1.22");
+        assert_eq!(decision("api.runtime").alternatives, vec!["rust@1.82"]);
+        assert_eq!(decision("edge.framework").choice, "Native");
+        assert!(decision("edge.runtime").alternatives.is_empty());
+
+        // Service costs are part of the estimate
+        let plain = Selector::new(get_test_rules(), 42, 8).unwrap();
+        let without = plain.select(&blueprint).unwrap();
+        assert_eq!(
+            plan.estimated.monthly_cost_usd,
+            without.estimated.monthly_cost_usd + 20.0
+        );
     }
 
     #[test]
-    fn test_persistence_constraints() {
-        let selector = Selector::new(get_test_rules(), 42, 8).unwrap();
+    fn test_services_are_filtered_by_constraints() {
+        let overlay = r#"
+candidates:
+  services:
+    api:
+      - { name: "Go API", regions: ["eu-west-1"] }
+"#;
+        let rules = get_test_rules_with_services();
+        let selector = Selector::from_layers(&[&rules, overlay], 42, 8)
+            .unwrap()
+            .with_explain(true);
         let mut blueprint = get_test_blueprint();
+        blueprint.constraints.region_allow = Some(vec!["us-east-1".to_string()]);
 
-        // Test SQL constraint
-        blueprint.constraints.persistence = Some(PersistenceType::Sql);
-        let result = selector.select(&blueprint);
-        assert!(result.is_ok());
-        let plan = result.unwrap();
-        assert_eq!(plan.stack.database, "PostgreSQL");
-
-        // Test KV constraint
-        blueprint.constraints.persistence = Some(PersistenceType::Kv);
-        let result = selector.select(&blueprint);
-        assert!(result.is_ok());
-        let plan = result.unwrap();
-        assert_eq!(plan.stack.database, "Redis");
+        let plan = selector.select(&blueprint).unwrap();
+        let api = plan
+            .decisions
+            .iter()
+            .find(|d| d.topic == "api.framework")
+            .unwrap();
+        assert_eq!(api.choice, "Axum");
+        assert_eq!(api.rejected[0].candidate, "Go API");
+        assert_eq!(api.rejected[0].filter, "region_allow");
 
-        // Test Both constraint
-        blueprint.constraints.persistence = Some(PersistenceType::Both);
-        let result = selector.select(&blueprint);
-        assert!(result.is_ok());
-        let plan = result.unwrap();
-        assert_eq!(plan.stack.database, "DynamoDB");
+        // A kind with no candidate left is left out of the stack
+        blueprint.single_language_mode = Some(LanguageMode::Rust);
+        let plan = selector.select(&blueprint).unwrap();
+        let services = plan.stack.services.unwrap();
+        assert_eq!(services.len(), 1);
+        assert_eq!(services[0].framework, "Axum");
+        let notes = plan.estimated.notes.unwrap();
+        assert!(notes.iter().any(|n| n.starts_with("No edge service")));
+
+        // Services count against the budget
+        blueprint.single_language_mode = None;
+        blueprint.constraints.region_allow = None;
+        let unconstrained = selector.select(&blueprint).unwrap();
+        blueprint.constraints.monthly_cost_usd_max =
+            Some(unconstrained.estimated.monthly_cost_usd - 1.0);
+        let plan = selector.select(&blueprint).unwrap();
+        assert!(plan.estimated.monthly_cost_usd < unconstrained.estimated.monthly_cost_usd);
     }
 
     #[test]
-    fn test_region_constraints() {
-        let selector = Selector::new(get_test_rules(), 42, 8).unwrap();
+    fn test_declared_services() {
+        let overlay = r#"
+candidates:
+  services:
+    worker:
+      - name: "Rust Cron"
+        language: "Rust"
+        framework: "tokio-cron"
+        runtime: "rust@1.82"
+        build: "cargo build"
+        tests: "cargo test"
+        metrics: { quality: 0.8, slo: 0.8, cost: 0.8, security: 0.8, ops: 0.8 }
+        regions: ["*"]
+        monthly_cost_base: 5
+        workloads: [cron]
+"#;
+        let rules = get_test_rules_with_services();
+        let selector = Selector::from_layers(&[&rules, overlay], 42, 8)
+            .unwrap()
+            .with_explain(true);
         let mut blueprint = get_test_blueprint();
+        blueprint.services = Some(Vec::new());
+        let without = selector.select(&blueprint).unwrap();
+        assert!(without.stack.services.unwrap().is_empty());
+
+        let services = r#"
+- { name: public-api, kind: api, count: 2 }
+- { name: admin-api, kind: api, constraints: { security_min: 0.82 } }
+- { name: scheduler, kind: worker, workload: cron }
+"#;
+        blueprint.services = Some(serde_yaml::from_str(services).unwrap());
+        let plan = selector.select(&blueprint).unwrap();
+        let decision = |topic: &str| plan.decisions.iter().find(|d| d.topic == topic);
+
+        // Declared services replace the defaults, so there is no edge service
+        let stack_services = plan.stack.services.clone().unwrap();
+        let names: Vec<&str> = stack_services.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["public-api", "admin-api", "scheduler"]);
+        assert!(decision("edge.framework").is_none());
+        assert_eq!(stack_services[0].count, 2);
+        assert_eq!(stack_services[2].workload, Some(Workload::Cron));
+
+        // Only the Go API clears the admin API's security floor
+        let admin = decision("admin-api.framework").unwrap();
+        assert_eq!(admin.choice, "Gin");
+        assert_eq!(admin.rejected[0].filter, "security_min");
+        assert_eq!(
+            decision("scheduler.framework").unwrap().choice,
+            "tokio-cron"
+        );
 
-        // Constrain to us-east-1 only
-        blueprint.constraints.region_allow = Some(vec!["us-east-1".to_string()]);
-
-        let result = selector.select(&blueprint);
-        assert!(result.is_ok());
+        // Two public API instances, one admin API and the scheduler
+        assert_eq!(
+            plan.estimated.monthly_cost_usd,
+            without.estimated.monthly_cost_usd + 35.0
+        );
 
-        // All selected components should support us-east-1 or be global
-        let plan = result.unwrap();
+        // A declared service without a suitable candidate fails the plan
+        let mut declared = blueprint.services.clone().unwrap();
+        declared.push(
+            serde_yaml::from_str("{ name: ingest, kind: worker, workload: stream_consumer }")
+                .unwrap(),
+        );
+        let mut failing = blueprint.clone();
+        failing.services = Some(declared);
+        match selector.select(&failing).unwrap_err() {
+            RuneforgeError::NoSuitableCandidates {
+                category,
+                rejections,
+            } => {
+                assert_eq!(category, "ingest");
+                assert_eq!(rejections[0].candidate, "Rust Cron");
+                assert_eq!(rejections[0].filter, "workload");
+                assert_eq!(rejections[0].required, "stream_consumer");
+            }
+            other => panic!("Expected NoSuitableCandidates, got {other:?}"),
+        }
 
-        // Frontend should be either SvelteKit (global) or Next.js (supports us-east-1)
-        assert!(plan.stack.frontend == "SvelteKit" || plan.stack.frontend == "Next.js");
+        // Without declared services, the rules' defaults apply
+        let defaults = r#"
+default_services:
+  - { name: jobs, kind: worker, when: { rps_peak_above: 500 } }
+"#;
+        let selector = Selector::from_layers(&[&rules, overlay, defaults], 42, 8).unwrap();
+        blueprint.services = None;
+        let plan = selector.select(&blueprint).unwrap();
+        let services = plan.stack.services.unwrap();
+        assert_eq!(services.len(), 1);
+        assert_eq!(
+            (services[0].name.as_str(), services[0].kind.as_str()),
+            ("jobs", "worker")
+        );
+        blueprint.traffic_profile.rps_peak = 100.0;
+        let plan = selector.select(&blueprint).unwrap();
+        assert!(plan.stack.services.unwrap().is_empty());
     }
 
     #[test]
-    fn test_cost_constraints() {
-        let selector = Selector::new(get_test_rules(), 42, 8).unwrap();
+    fn test_adjustments_come_from_rules() {
+        let overlay = r#"
+adjustments:
+  - { when: { latency_sensitive: true }, metric: security, weight: 1.0 }
+"#;
+        let rules = get_test_rules_with_services();
+        let selector = Selector::from_layers(&[&rules, overlay], 42, 8)
+            .unwrap()
+            .with_explain(true);
         let mut blueprint = get_test_blueprint();
 
-        // Set very low cost constraint
-        blueprint.constraints.monthly_cost_usd_max = Some(100.0);
+        let plan = selector.select(&blueprint).unwrap();
+        let breakdown = plan.decisions[0].breakdown.as_ref().unwrap();
+        assert!(breakdown.adjustments.is_empty());
+        // Scores are divided by every weight the rules can raise, matched or not
+        assert!((breakdown.normalization - 2.0).abs() < 1e-9);
 
-        let result = selector.select(&blueprint);
-        assert!(result.is_err());
-        let err_msg = result.unwrap_err();
-        // Check that it's a cost constraint error
-        assert!(
-            err_msg.contains("cost constraint") || err_msg.contains("No suitable"),
-            "Expected cost constraint error, got: {err_msg}"
+        blueprint.traffic_profile.latency_sensitive = true;
+        let plan = selector.select(&blueprint).unwrap();
+        let breakdown = plan.decisions[0].breakdown.as_ref().unwrap();
+        assert_eq!(
+            breakdown.adjustments,
+            vec!["security +1 when latency_sensitive"]
         );
+        assert!((breakdown.normalization - 2.0).abs() < 1e-9);
+        let security = breakdown.metrics.iter().find(|m| m.metric == "security");
+        assert!((security.unwrap().weight - 1.15).abs() < 1e-9);
+
+        // Services are scored by the same model
+        for config in &selector.rank_services(&blueprint).unwrap()[0].configurations {
+            let breakdown = selector.score_breakdown(&config.candidate.metrics, &blueprint);
+            assert_eq!(config.score, breakdown.base_score());
+        }
+    }
+
+    #[test]
+    fn test_weighted_sum_reproduces_baseline_scores() {
+        let rules: Rules = serde_yaml::from_str(crate::rules_source::EMBEDDED_RULES).unwrap();
+        let selector = Selector::from_rules(rules.clone(), 42, 8).unwrap();
+        let mut blueprint: Blueprint =
+            serde_yaml::from_str(include_str!("../examples/baseline.yaml")).unwrap();
+        let weights = &rules.weights;
+
+        // The formula before adjustments moved into rules: fixed bonuses
+        // for latency-sensitive and global traffic, divided by 1.15
+        for (latency_sensitive, global) in [(false, false), (true, false), (true, true)] {
+            blueprint.traffic_profile.latency_sensitive = latency_sensitive;
+            blueprint.traffic_profile.global = global;
+            let plan = selector.select(&blueprint).unwrap();
+
+            let mut checked = 0;
+            for decision in &plan.decisions {
+                let Some(candidate) = rules
+                    .candidates
+                    .get(&decision.topic)
+                    .iter()
+                    .find(|c| c.name == decision.choice)
+                else {
+                    continue;
+                };
+                let m = &candidate.metrics;
+                let mut score = weights.quality * m.quality
+                    + weights.slo * m.slo
+                    + weights.cost * m.cost
+                    + weights.security * m.security
+                    + weights.ops * m.ops;
+                if latency_sensitive {
+                    score += 0.1 * m.slo;
+                }
+                if global {
+                    score += 0.05 * m.ops;
+                }
+                assert!(
+                    (decision.score - score / 1.15).abs() < 1e-9,
+                    "{}: {} != {}",
+                    decision.topic,
+                    decision.score,
+                    score / 1.15
+                );
+                checked += 1;
+            }
+            assert!(checked >= 5);
+        }
+    }
+
+    #[test]
+    fn test_scoring_strategies() {
+        let blueprint = get_test_blueprint();
+        let explained = |strategy| {
+            Selector::new(get_test_rules(), 42, 8)
+                .unwrap()
+                .with_explain(true)
+                .with_scoring(strategy)
+                .select(&blueprint)
+                .unwrap()
+        };
+
+        for strategy in [
+            ScoringStrategy::WeightedSum,
+            ScoringStrategy::WeightedGeometric,
+            ScoringStrategy::Lexicographic,
+            ScoringStrategy::Topsis,
+        ] {
+            let plan = explained(strategy);
+            validate_stack_plan(&plan).unwrap();
+            for decision in &plan.decisions {
+                let breakdown = decision.breakdown.as_ref().unwrap();
+                assert_eq!(breakdown.strategy, strategy);
+                assert!((breakdown.score() - decision.score).abs() < 1e-9);
+            }
+        }
+
+        // Security first: the most secure cache wins however weak it is
+        let hardened = r#"
+candidates:
+  cache:
+    - name: "Hardened Cache"
+      metrics: { quality: 0.3, slo: 0.3, cost: 0.3, security: 0.99, ops: 0.3 }
+      regions: ["*"]
+"#;
+        let selector = Selector::from_layers(&[get_test_rules(), hardened], 42, 8).unwrap();
+        let plan = selector.select(&blueprint).unwrap();
+        assert_eq!(plan.stack.choice("cache"), Some("Redis"));
+
+        let lexicographic = "scoring: { strategy: lexicographic, priority: [security, cost] }";
+        let selector =
+            Selector::from_layers(&[get_test_rules(), hardened, lexicographic], 42, 8).unwrap();
+        let plan = selector.select(&blueprint).unwrap();
+        assert_eq!(plan.stack.choice("cache"), Some("Hardened Cache"));
     }
 
     #[test]
@@ -1042,22 +4019,74 @@ compliance_requirements:
         let mut blueprint = get_test_blueprint();
 
         // Set preferences
-        blueprint.prefs = Some(Preferences {
-            frontend: Some(vec!["Next.js".to_string()]),
-            backend: Some(vec!["Axum".to_string()]),
-            database: Some(vec!["Redis".to_string()]),
-            ai: Some(vec!["Claude".to_string()]),
-        });
+        let mut prefs = Preferences::default();
+        for (topic, name) in [
+            ("frontend", "Next.js"),
+            ("backend", "Axum"),
+            ("database", "Redis"),
+            ("ai", "Claude"),
+        ] {
+            prefs
+                .ranked
+                .insert(topic.to_string(), vec![name.to_string()]);
+        }
+        blueprint.prefs = Some(prefs);
         blueprint.single_language_mode = Some(LanguageMode::Rust);
 
         let result = selector.select(&blueprint);
         assert!(result.is_ok());
 
         let plan = result.unwrap();
-        assert_eq!(plan.stack.frontend, "Next.js");
-        assert_eq!(plan.stack.backend, "Axum");
-        assert_eq!(plan.stack.database, "Redis");
-        assert!(plan.stack.ai.contains(&"Claude".to_string()));
+        assert_eq!(plan.stack.choice("frontend"), Some("Next.js"));
+        assert_eq!(plan.stack.choice("backend"), Some("Axum"));
+        assert_eq!(plan.stack.choice("database"), Some("Redis"));
+        assert!(plan.stack.choices("ai").contains(&"Claude"));
+    }
+
+    #[test]
+    fn test_avoid_lists_and_ranked_preferences() {
+        let selector = Selector::new(&get_test_rules_with_services(), 42, 8).unwrap();
+        let mut blueprint = get_test_blueprint();
+        let mut prefs = Preferences::default();
+        prefs.ranked.insert(
+            "cache".to_string(),
+            vec!["Memcached".to_string(), "Redis".to_string()],
+        );
+        prefs
+            .avoid
+            .insert("api".to_string(), vec!["Gin".to_string()]);
+        prefs
+            .avoid
+            .insert("database".to_string(), vec!["PostgreSQL".to_string()]);
+        blueprint.prefs = Some(prefs);
+
+        let plan = selector.select(&blueprint).unwrap();
+        assert_eq!(plan.stack.choice("cache"), Some("Memcached"));
+        assert_ne!(plan.stack.choice("database"), Some("PostgreSQL"));
+        assert_eq!(plan.stack.services.unwrap()[0].framework, "Axum");
+        let cache = plan.decisions.iter().find(|d| d.topic == "cache").unwrap();
+        assert!(cache
+            .reasons
+            .contains(&"Ranked #1 in team preferences".to_string()));
+        let database = plan
+            .decisions
+            .iter()
+            .find(|d| d.topic == "database")
+            .unwrap();
+        assert!(!database.alternatives.contains(&"PostgreSQL".to_string()));
+
+        // Without any bonus strength the ranking no longer changes the outcome
+        blueprint.prefs.as_mut().unwrap().strength = Some(0.0);
+        let plan = selector.select(&blueprint).unwrap();
+        assert_eq!(plan.stack.choice("cache"), Some("Redis"));
+
+        // Avoid lists are hard exclusions and show up in the diagnostic
+        blueprint.constraints.persistence = Some(PersistenceType::Sql);
+        let err = selector.select(&blueprint).unwrap_err().to_string();
+        assert!(err.contains("No suitable database candidates found"));
+        assert!(
+            err.contains("PostgreSQL: prefs.avoid (required not listed in prefs.avoid.database")
+        );
     }
 
     #[test]
@@ -1083,6 +4112,85 @@ compliance_requirements:
         }
     }
 
+    #[test]
+    fn test_explain_breakdown_matches_scores() {
+        let mut blueprint = get_test_blueprint();
+        let mut prefs = Preferences::default();
+        prefs
+            .ranked
+            .insert("cache".to_string(), vec!["Memcached".to_string()]);
+        blueprint.prefs = Some(prefs);
+
+        let plain = Selector::new(get_test_rules(), 42, 8).unwrap();
+        let plan = plain.select(&blueprint).unwrap();
+        assert!(plan.decisions.iter().all(|d| d.breakdown.is_none()));
+
+        let explained = Selector::new(get_test_rules(), 42, 8)
+            .unwrap()
+            .with_explain(true);
+        let plan = explained.select(&blueprint).unwrap();
+        for decision in &plan.decisions {
+            let breakdown = decision.breakdown.as_ref().unwrap();
+            assert!((breakdown.score() - decision.score).abs() < 1e-9);
+            assert_eq!(breakdown.adjustments, vec!["ops +0.05 when global"]);
+            assert!((breakdown.normalization - 1.15).abs() < 1e-9);
+            assert!(decision.score <= 1.0);
+        }
+
+        let cache = plan.decisions.iter().find(|d| d.topic == "cache").unwrap();
+        assert_eq!(cache.choice, "Memcached");
+        assert!(cache.breakdown.as_ref().unwrap().preference_bonus > 0.0);
+    }
+
+    #[test]
+    fn test_explain_lists_rejected_candidates() {
+        let selector = Selector::new(get_test_rules(), 42, 8)
+            .unwrap()
+            .with_explain(true);
+        let mut blueprint = get_test_blueprint();
+        blueprint.single_language_mode = Some(LanguageMode::Rust);
+        blueprint.constraints.persistence = Some(PersistenceType::Sql);
+        blueprint.constraints.region_allow = Some(vec!["us-west-2".to_string()]);
+
+        let plan = selector.select(&blueprint).unwrap();
+        let rejected = |topic: &str, candidate: &str| {
+            plan.decisions
+                .iter()
+                .find(|d| d.topic == topic)
+                .unwrap()
+                .rejected
+                .iter()
+                .find(|r| r.candidate == candidate)
+                .cloned()
+                .unwrap()
+        };
+
+        let go = rejected("language", "Go");
+        assert_eq!(go.filter, "single_language_mode");
+        assert_eq!((go.required.as_str(), go.actual.as_str()), ("Rust", "Go"));
+
+        let gin = rejected("backend", "Gin");
+        assert_eq!(gin.filter, "requires.language");
+        assert_eq!((gin.required.as_str(), gin.actual.as_str()), ("Go", "Rust"));
+
+        let redis = rejected("database", "Redis");
+        assert_eq!(redis.filter, "persistence");
+        assert_eq!(
+            (redis.required.as_str(), redis.actual.as_str()),
+            ("sql", "kv")
+        );
+
+        let next = rejected("frontend", "Next.js");
+        assert_eq!(next.filter, "region_allow");
+        assert_eq!(next.required, "one of [us-west-2]");
+        assert_eq!(next.actual, "[us-east-1, eu-west-1]");
+
+        // Without --explain the trace is left out
+        let plain = Selector::new(get_test_rules(), 42, 8).unwrap();
+        let plan = plain.select(&blueprint).unwrap();
+        assert!(plan.decisions.iter().all(|d| d.rejected.is_empty()));
+    }
+
     #[test]
     fn test_latency_sensitive_scoring() {
         let selector = Selector::new(get_test_rules(), 42, 8).unwrap();
@@ -1096,7 +4204,7 @@ compliance_requirements:
 
         // Check that high SLO components are preferred
         // Redis cache should be selected for its high SLO score
-        assert_eq!(plan.stack.cache, "Redis");
+        assert_eq!(plan.stack.choice("cache"), Some("Redis"));
     }
 
     #[test]
@@ -1110,7 +4218,7 @@ compliance_requirements:
         let plan = result.unwrap();
 
         // Should select 2 AI providers
-        assert_eq!(plan.stack.ai.len(), 2);
+        assert_eq!(plan.stack.choices("ai").len(), 2);
 
         // Find the AI decision
         let ai_decision = plan
@@ -1123,6 +4231,236 @@ compliance_requirements:
         assert!(!ai_decision.alternatives.is_empty());
     }
 
+    #[test]
+    fn test_rules_defined_categories() {
+        let overlay = r#"
+categories:
+  - name: auth
+    depends_on: [language]
+  - name: search
+    cardinality: optional
+  - name: ai
+    count: 3
+candidates:
+  auth:
+    - name: "Rust Auth"
+      requires: { language: "Rust" }
+      metrics: { quality: 0.8, slo: 0.8, cost: 0.8, security: 0.9, ops: 0.8 }
+      regions: ["*"]
+    - name: "Go Auth"
+      requires: { language: "Go" }
+      metrics: { quality: 0.9, slo: 0.9, cost: 0.9, security: 0.9, ops: 0.9 }
+      regions: ["*"]
+  search:
+    - name: "Search Cluster"
+      metrics: { quality: 0.9, slo: 0.9, cost: 0.5, security: 0.9, ops: 0.8 }
+      regions: ["*"]
+      monthly_cost_base: 5000
+"#;
+        let rules = include_str!("../resources/rules.yaml");
+        let selector = Selector::from_layers(&[rules, overlay], 42, 8).unwrap();
+        let mut blueprint = get_test_blueprint();
+        blueprint.constraints.monthly_cost_usd_max = Some(2000.0);
+
+        // Auth follows the language it requires; TypeScript has none
+        let plan = selector.select(&blueprint).unwrap();
+        let auth = plan.stack.choice("auth").unwrap();
+        assert_eq!(auth, format!("{} Auth", plan.stack.language));
+        assert_eq!(plan.stack.choices("ai").len(), 3);
+
+        // The optional search cluster is over budget, so it is left out
+        assert!(plan.stack.choices("search").is_empty());
+        assert_eq!(plan.stack.components["search"], StackEntry::None);
+        let search = plan.decisions.iter().find(|d| d.topic == "search").unwrap();
+        assert_eq!(search.choice, "none");
+        assert_eq!(
+            search.reasons,
+            vec!["Optional, and no candidate passed the filters"]
+        );
+
+        blueprint.single_language_mode = Some(LanguageMode::Rust);
+        let plan = selector.select(&blueprint).unwrap();
+        assert_eq!(plan.stack.choice("auth"), Some("Rust Auth"));
+        let decision = plan.decisions.iter().find(|d| d.topic == "auth").unwrap();
+        assert!(decision
+            .reasons
+            .contains(&"Compatible with Rust language".to_string()));
+        let json = serde_json::to_value(&plan.stack).unwrap();
+        assert_eq!(json["auth"], "Rust Auth");
+        assert_eq!(json["ai"].as_array().unwrap().len(), 3);
+        validate_stack_plan(&plan).unwrap();
+
+        // Marked required, the search cluster must be part of the stack
+        blueprint
+            .categories
+            .insert("search".to_string(), CategoryMode::Required);
+        let err = selector.select(&blueprint).unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("No suitable search candidates found"));
+        blueprint.categories.clear();
+
+        blueprint.single_language_mode = Some(LanguageMode::Ts);
+        let err = selector.select(&blueprint).unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("No suitable auth candidates found"));
+    }
+
+    #[test]
+    fn test_category_modes() {
+        let selector = Selector::new(get_test_rules(), 42, 8).unwrap();
+        let full = selector.select(&get_test_blueprint()).unwrap();
+        let mut blueprint = get_test_blueprint();
+        blueprint.categories = [
+            ("frontend", CategoryMode::Auto),
+            ("cache", CategoryMode::Auto),
+            ("queue", CategoryMode::None),
+            ("ai", CategoryMode::Auto),
+        ]
+        .into_iter()
+        .map(|(name, mode)| (name.to_string(), mode))
+        .collect();
+
+        let plan = selector.select(&blueprint).unwrap();
+        let reasons = |plan: &StackPlan, topic: &str| {
+            let decision = plan.decisions.iter().find(|d| d.topic == topic).unwrap();
+            decision.reasons.clone()
+        };
+        assert!(plan.stack.choice("frontend").is_some());
+        assert_eq!(
+            reasons(&plan, "frontend")[0],
+            "Needed: goal 'Build a web app' mentions web"
+        );
+        assert_eq!(
+            reasons(&plan, "queue"),
+            vec!["Not needed: the blueprint marks queue as none"]
+        );
+        assert_eq!(
+            reasons(&plan, "cache"),
+            vec!["Not needed: no goal mentions cache, caching, and none of [latency_sensitive; rps_peak > 1000] applies"]
+        );
+        assert!(plan.stack.choices("ai").is_empty());
+        let json = serde_json::to_value(&plan.stack).unwrap();
+        assert!(json["queue"].is_null() && json["ai"].is_null());
+        assert!(plan.estimated.monthly_cost_usd < full.estimated.monthly_cost_usd);
+        validate_stack_plan(&plan).unwrap();
+
+        // Goals and traffic bring categories back
+        blueprint
+            .goals
+            .push("Recommendations powered by an LLM".to_string());
+        blueprint.traffic_profile.latency_sensitive = true;
+        let plan = selector.select(&blueprint).unwrap();
+        assert_eq!(plan.stack.choices("ai").len(), 2);
+        assert_eq!(
+            reasons(&plan, "ai")[0],
+            "Needed: goal 'Recommendations powered by an LLM' mentions llm"
+        );
+        assert_eq!(reasons(&plan, "cache")[0], "Needed: latency_sensitive");
+
+        blueprint
+            .categories
+            .insert("language".to_string(), CategoryMode::None);
+        let err = selector.select(&blueprint).unwrap_err();
+        assert!(err.to_string().contains("language cannot be none"));
+        blueprint.categories.remove("language");
+        blueprint
+            .categories
+            .insert("search".to_string(), CategoryMode::Required);
+        let err = selector.select(&blueprint).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid blueprint at /categories/search: unknown category 'search'"
+        );
+    }
+
+    #[test]
+    fn test_mentions_matches_words_and_plurals() {
+        assert!(mentions("Run background jobs", "job"));
+        assert!(mentions("A real-time dashboard", "real time"));
+        assert!(mentions("Add AI search", "ai"));
+        assert!(!mentions("Maintain the platform", "ai"));
+        assert!(!mentions("Build a website", "web"));
+    }
+
+    #[test]
+    fn test_declared_dimensions_are_scored() {
+        let overlay = r#"
+weights: { quality: 0.15, slo: 0.1, cost: 0.1, security: 0.1, ops: 0.05 }
+dimensions:
+  - { name: carbon_footprint, weight: 0.5, default: 0.2 }
+candidates:
+  cache:
+    - name: Memcached
+      metrics: { carbon_footprint: 1.0 }
+"#;
+        let rules = include_str!("../resources/rules.yaml");
+        let selector = Selector::from_layers(&[rules, overlay], 42, 8)
+            .unwrap()
+            .with_explain(true);
+        let plan = selector.select(&get_test_blueprint()).unwrap();
+
+        let cache = plan.decisions.iter().find(|d| d.topic == "cache").unwrap();
+        assert_eq!(cache.choice, "Memcached");
+        let breakdown = cache.breakdown.as_ref().unwrap();
+        assert_eq!(breakdown.metrics.len(), 6);
+        assert_eq!(breakdown.metrics[5].metric, "carbon_footprint");
+        assert_eq!(breakdown.metrics[5].contribution, 0.5);
+        assert!((breakdown.score() - cache.score).abs() < 1e-9);
+
+        // Candidates that do not rate the dimension get its default
+        let database = plan.decisions.iter().find(|d| d.topic == "database");
+        let breakdown = database.unwrap().breakdown.as_ref().unwrap();
+        assert_eq!(breakdown.metrics[5].value, 0.2);
+    }
+
+    #[test]
+    fn test_category_order_and_errors() {
+        let mut rules: Rules = serde_yaml::from_str(get_test_rules()).unwrap();
+        let order: Vec<&str> = rules
+            .category_order()
+            .unwrap()
+            .iter()
+            .map(|c| c.name.as_str())
+            .collect();
+        assert_eq!(order[..2], ["language", "backend"]);
+
+        // Dependencies come first whatever the declaration order
+        rules.categories.rotate_left(1);
+        let order: Vec<&str> = rules
+            .category_order()
+            .unwrap()
+            .iter()
+            .map(|c| c.name.as_str())
+            .collect();
+        assert_eq!(
+            order,
+            [
+                "frontend", "database", "cache", "queue", "ai", "infra", "ci_cd", "language",
+                "backend"
+            ]
+        );
+
+        // backend -> language -> frontend -> backend
+        for category in &mut rules.categories {
+            match category.name.as_str() {
+                "language" => category.depends_on = vec!["frontend".to_string()],
+                "frontend" => category.depends_on = vec!["backend".to_string()],
+                _ => {}
+            }
+        }
+        let err = Selector::from_rules(rules.clone(), 42, 8).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Failed to parse rules: category dependencies form a cycle through backend, frontend, language"
+        );
+
+        rules.categories.retain(|c| c.name != "language");
+        let err = Selector::from_rules(rules, 42, 8).unwrap_err();
+        assert!(err.to_string().contains("unknown category 'language'"));
+    }
+
     #[test]
     fn test_compliance_reasons() {
         let selector = Selector::new(get_test_rules(), 42, 8).unwrap();
@@ -1143,6 +4481,60 @@ compliance_requirements:
         assert!(has_compliance_reason);
     }
 
+    #[test]
+    fn test_compliance_features_filter_candidates() {
+        let selector = Selector::new(get_test_rules(), 42, 8).unwrap();
+        let mut blueprint = get_test_blueprint();
+        blueprint.constraints.compliance = Some(vec![ComplianceType::Hipaa, ComplianceType::Sox]);
+
+        let plan = selector.select(&blueprint).unwrap();
+        assert!(matches!(
+            plan.stack.choice("database"),
+            Some("PostgreSQL" | "DynamoDB")
+        ));
+        assert_eq!(plan.stack.choice("ci_cd"), Some("GitHub Actions"));
+
+        let database = plan
+            .decisions
+            .iter()
+            .find(|d| d.topic == "database")
+            .unwrap();
+        assert!(!database.alternatives.contains(&"Redis".to_string()));
+
+        // Redis is the only KV store and lacks the HIPAA features
+        blueprint.constraints.persistence = Some(PersistenceType::Kv);
+        let result = selector.select(&blueprint);
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("No suitable database candidates found"));
+    }
+
+    #[test]
+    fn test_metric_floors_filter_candidates() {
+        let selector = Selector::new(get_test_rules(), 42, 8).unwrap();
+        let mut blueprint = get_test_blueprint();
+        let mut floors = std::collections::BTreeMap::new();
+        floors.insert("database".to_string(), 0.9);
+        blueprint.constraints.security_min = Some(MetricFloor::PerCategory(floors));
+
+        let plan = selector.select(&blueprint).unwrap();
+        assert_eq!(plan.stack.choice("database"), Some("PostgreSQL"));
+        let database = plan
+            .decisions
+            .iter()
+            .find(|d| d.topic == "database")
+            .unwrap();
+        assert!(database.alternatives.is_empty());
+        // Floors scoped to database leave other categories alone
+        assert_eq!(plan.stack.choice("cache"), Some("Redis"));
+
+        blueprint.constraints.security_min = Some(MetricFloor::All(0.95));
+        let err = selector.select(&blueprint).unwrap_err().to_string();
+        assert!(err.contains("No suitable"));
+        assert!(err.contains("Actix Web: security_min (required >= 0.95, actual 0.8)"));
+    }
+
     #[test]
     fn test_deterministic_selection() {
         let selector1 = Selector::new(get_test_rules(), 42, 8).unwrap();
@@ -1154,14 +4546,7 @@ compliance_requirements:
 
         // Same seed should produce same results
         assert_eq!(result1.stack.language, result2.stack.language);
-        assert_eq!(result1.stack.frontend, result2.stack.frontend);
-        assert_eq!(result1.stack.backend, result2.stack.backend);
-        assert_eq!(result1.stack.database, result2.stack.database);
-        assert_eq!(result1.stack.cache, result2.stack.cache);
-        assert_eq!(result1.stack.queue, result2.stack.queue);
-        assert_eq!(result1.stack.ai, result2.stack.ai);
-        assert_eq!(result1.stack.infra, result2.stack.infra);
-        assert_eq!(result1.stack.ci_cd, result2.stack.ci_cd);
+        assert_eq!(result1.stack.components, result2.stack.components);
     }
 
     #[test]
@@ -1178,13 +4563,19 @@ compliance_requirements:
 
         // Different seeds might produce different results when there are ties
         // At least one component should be different (backend has two Rust options with similar scores)
-        let _all_same = result1.stack.backend == result2.stack.backend;
+        let _all_same = result1.stack.choice("backend") == result2.stack.choice("backend");
 
         // This test might occasionally pass even with different seeds,
         // but it's statistically unlikely all components would be the same
         // We'll just verify both are valid selections
-        assert!(result1.stack.backend == "Actix Web" || result1.stack.backend == "Axum");
-        assert!(result2.stack.backend == "Actix Web" || result2.stack.backend == "Axum");
+        assert!(matches!(
+            result1.stack.choice("backend"),
+            Some("Actix Web" | "Axum")
+        ));
+        assert!(matches!(
+            result2.stack.choice("backend"),
+            Some("Actix Web" | "Axum")
+        ));
     }
 
     #[test]
@@ -1216,11 +4607,21 @@ candidates:
         let mut blueprint = get_test_blueprint();
         blueprint.constraints.region_allow = Some(vec!["us-east-1".to_string()]);
 
-        let result = selector.select(&blueprint);
-        assert!(result.is_err());
-        assert!(result
-            .unwrap_err()
+        let err = selector.select(&blueprint).unwrap_err();
+        assert!(err
+            .to_string()
             .contains("No suitable language candidates found"));
+        match err {
+            RuneforgeError::NoSuitableCandidates {
+                category,
+                rejections,
+            } => {
+                assert_eq!(category, "language");
+                assert_eq!(rejections[0].candidate, "Rust");
+                assert_eq!(rejections[0].filter, "region_allow");
+            }
+            other => panic!("Expected NoSuitableCandidates, got {other:?}"),
+        }
     }
 
     #[test]
@@ -1239,6 +4640,36 @@ candidates:
         assert!(plan.estimated.monthly_cost_usd < 1000.0); // Within constraint
     }
 
+    #[test]
+    fn test_traffic_scaled_costs() {
+        // Redis needs one instance per 1000 read requests per second
+        let rules = get_test_rules().replacen(
+            "      monthly_cost_base: 100\n    - name: \"Memcached\"",
+            "      monthly_cost_base: 100\n      pricing: { serves: reads, instance_rps: 1000, instance_monthly: 50 }\n    - name: \"Memcached\"",
+            1,
+        );
+        let selector = Selector::new(&rules, 42, 8).unwrap();
+        let mut blueprint = get_test_blueprint();
+        blueprint.constraints.monthly_cost_usd_max = None;
+
+        let plan = selector.select(&blueprint).unwrap();
+        assert_eq!(plan.stack.choice("cache"), Some("Redis"));
+        assert_eq!(plan.estimated.monthly_cost_usd, 925.0);
+        assert_eq!(plan.estimated.egress_gb, Some(829.44));
+
+        // Ten times the traffic needs eight instances for 8000 read RPS
+        blueprint.traffic_profile.rps_peak = 10000.0;
+        let plan = selector.select(&blueprint).unwrap();
+        assert_eq!(plan.estimated.monthly_cost_usd, 1275.0);
+        assert_eq!(plan.estimated.egress_gb, Some(8294.4));
+
+        // Under a budget the instance cost pushes the cache to Memcached
+        blueprint.constraints.monthly_cost_usd_max = Some(1000.0);
+        let plan = selector.select(&blueprint).unwrap();
+        assert_eq!(plan.stack.choice("cache"), Some("Memcached"));
+        assert_eq!(plan.estimated.monthly_cost_usd, 855.0);
+    }
+
     #[test]
     fn test_empty_goals() {
         let selector = Selector::new(get_test_rules(), 42, 8).unwrap();
@@ -1264,7 +4695,7 @@ candidates:
 
         let plan = result.unwrap();
         assert_eq!(plan.stack.language, "TypeScript");
-        assert_eq!(plan.stack.backend, "Express"); // Only TS backend option
+        assert_eq!(plan.stack.choice("backend"), Some("Express")); // Only TS backend option
     }
 
     #[test]
@@ -1331,6 +4762,9 @@ candidates:
 
         let plan = result.unwrap();
         // Verify a backend was selected from the tied options
-        assert!(["Option1", "Option2", "Option3"].contains(&plan.stack.backend.as_str()));
+        assert!(matches!(
+            plan.stack.choice("backend"),
+            Some("Option1" | "Option2" | "Option3")
+        ));
     }
 }
@@ -62,24 +62,52 @@ pub fn calculate_plan_hash(plan_json: &str) -> String {
     format!("sha256:{}", hex::encode(result))
 }
 
+/// Calculate rules hash so a plan can be traced to the rules that produced it
+pub fn calculate_rules_hash(rules_content: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(rules_content.as_bytes());
+    let result = hasher.finalize();
+    format!("sha256:{}", hex::encode(result))
+}
+
+/// Index combinations of `size` items out of `count`, in lexicographic order.
+pub(crate) fn combinations(count: usize, size: usize) -> Vec<Vec<usize>> {
+    if size == 0 {
+        return vec![Vec::new()];
+    }
+    let mut result = Vec::new();
+    for first in 0..count {
+        for rest in combinations(count - first - 1, size - 1) {
+            let mut combination = vec![first];
+            combination.extend(rest.into_iter().map(|i| i + first + 1));
+            result.push(combination);
+        }
+    }
+    result
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::schema::*;
+    use std::collections::BTreeMap;
 
     #[test]
     fn test_deterministic_hash() {
         let data = Stack {
             language: "rust".to_string(),
             services: None,
-            frontend: "SvelteKit".to_string(),
-            backend: "Actix Web".to_string(),
-            database: "PostgreSQL".to_string(),
-            cache: "Redis".to_string(),
-            queue: "NATS".to_string(),
-            ai: vec!["RuneSage".to_string()],
-            infra: "Terraform".to_string(),
-            ci_cd: "GitHub Actions".to_string(),
+            data_roles: None,
+            components: BTreeMap::from([
+                ("frontend".to_string(), "SvelteKit".into()),
+                ("backend".to_string(), "Actix Web".into()),
+                ("database".to_string(), "PostgreSQL".into()),
+                ("cache".to_string(), "Redis".into()),
+                ("queue".to_string(), "NATS".into()),
+                ("ai".to_string(), vec!["RuneSage"].into()),
+                ("infra".to_string(), "Terraform".into()),
+                ("ci_cd".to_string(), "GitHub Actions".into()),
+            ]),
         };
 
         let hash1 = calculate_hash(&data).unwrap();
@@ -93,27 +121,33 @@ mod tests {
         let data1 = Stack {
             language: "rust".to_string(),
             services: None,
-            frontend: "SvelteKit".to_string(),
-            backend: "Actix Web".to_string(),
-            database: "PostgreSQL".to_string(),
-            cache: "Redis".to_string(),
-            queue: "NATS".to_string(),
-            ai: vec!["RuneSage".to_string()],
-            infra: "Terraform".to_string(),
-            ci_cd: "GitHub Actions".to_string(),
+            data_roles: None,
+            components: BTreeMap::from([
+                ("frontend".to_string(), "SvelteKit".into()),
+                ("backend".to_string(), "Actix Web".into()),
+                ("database".to_string(), "PostgreSQL".into()),
+                ("cache".to_string(), "Redis".into()),
+                ("queue".to_string(), "NATS".into()),
+                ("ai".to_string(), vec!["RuneSage"].into()),
+                ("infra".to_string(), "Terraform".into()),
+                ("ci_cd".to_string(), "GitHub Actions".into()),
+            ]),
         };
 
         let data2 = Stack {
             language: "go".to_string(), // Different language
             services: None,
-            frontend: "SvelteKit".to_string(),
-            backend: "Actix Web".to_string(),
-            database: "PostgreSQL".to_string(),
-            cache: "Redis".to_string(),
-            queue: "NATS".to_string(),
-            ai: vec!["RuneSage".to_string()],
-            infra: "Terraform".to_string(),
-            ci_cd: "GitHub Actions".to_string(),
+            data_roles: None,
+            components: BTreeMap::from([
+                ("frontend".to_string(), "SvelteKit".into()),
+                ("backend".to_string(), "Actix Web".into()),
+                ("database".to_string(), "PostgreSQL".into()),
+                ("cache".to_string(), "Redis".into()),
+                ("queue".to_string(), "NATS".into()),
+                ("ai".to_string(), vec!["RuneSage"].into()),
+                ("infra".to_string(), "Terraform".into()),
+                ("ci_cd".to_string(), "GitHub Actions".into()),
+            ]),
         };
 
         let hash1 = calculate_hash(&data1).unwrap();
@@ -127,14 +161,17 @@ mod tests {
         let data = Stack {
             language: "rust".to_string(),
             services: None,
-            frontend: "SvelteKit".to_string(),
-            backend: "Actix Web".to_string(),
-            database: "PostgreSQL".to_string(),
-            cache: "Redis".to_string(),
-            queue: "NATS".to_string(),
-            ai: vec!["RuneSage".to_string()],
-            infra: "Terraform".to_string(),
-            ci_cd: "GitHub Actions".to_string(),
+            data_roles: None,
+            components: BTreeMap::from([
+                ("frontend".to_string(), "SvelteKit".into()),
+                ("backend".to_string(), "Actix Web".into()),
+                ("database".to_string(), "PostgreSQL".into()),
+                ("cache".to_string(), "Redis".into()),
+                ("queue".to_string(), "NATS".into()),
+                ("ai".to_string(), vec!["RuneSage"].into()),
+                ("infra".to_string(), "Terraform".into()),
+                ("ci_cd".to_string(), "GitHub Actions".into()),
+            ]),
         };
 
         let hash = calculate_hash(&data).unwrap();
@@ -412,4 +449,11 @@ mod tests {
         let hex_part = &hash[7..];
         assert_eq!(hex_part.len(), 64);
     }
+
+    #[test]
+    fn test_combinations() {
+        assert_eq!(combinations(3, 1), vec![vec![0], vec![1], vec![2]]);
+        assert_eq!(combinations(3, 2), vec![vec![0, 1], vec![0, 2], vec![1, 2]]);
+        assert!(combinations(1, 2).is_empty());
+    }
 }
@@ -24,6 +24,33 @@ fn test_cost_constraint_respected() {
         "Estimated cost {estimated_cost} exceeds constraint of 50 USD");
 }
 
+/// Test that declared services are planned within the budget
+#[test]
+fn test_services_count_against_budget() {
+    let fixture = "tests/acceptance/fixtures/valid_services_budget.yaml";
+    
+    let output = Command::new("cargo")
+        .args(["run", "--", "plan", "-f", fixture])
+        .output()
+        .expect("Failed to execute command");
+    
+    assert_eq!(output.status.code(), Some(0));
+    
+    let json: Value = serde_json::from_slice(&output.stdout)
+        .expect("Failed to parse JSON output");
+    
+    let services = json["stack"]["services"].as_array()
+        .expect("No services in output");
+    assert_eq!(services.len(), 1);
+    assert_eq!(services[0]["name"], "api");
+    
+    // Cost constraint in fixture is 75 USD, services included
+    let estimated_cost = json["estimated"]["monthly_cost_usd"].as_f64()
+        .expect("No monthly_cost_usd in output");
+    assert!(estimated_cost <= 75.0, 
+        "Estimated cost {estimated_cost} exceeds constraint of 75 USD");
+}
+
 /// Test that region constraints are respected
 #[test]
 fn test_region_constraint_respected() {
@@ -145,4 +172,35 @@ traffic_profile:
     
     // Clean up
     std::fs::remove_file("tests/acceptance/fixtures/impossible_constraints.yaml").ok();
-}
\ No newline at end of file
+}
+/// Test that every shipped blueprint plans successfully
+#[test]
+fn test_shipped_blueprints_are_feasible() {
+    let mut fixtures = Vec::new();
+    for (dir, prefix) in [
+        ("fixtures", ""),
+        ("examples", ""),
+        ("tests/acceptance/fixtures", "valid_"),
+    ] {
+        for entry in std::fs::read_dir(dir).expect("Failed to read fixture directory") {
+            let path = entry.expect("Failed to read directory entry").path();
+            let name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+            if name.starts_with(prefix) && name.ends_with(".yaml") {
+                fixtures.push(path);
+            }
+        }
+    }
+    fixtures.sort();
+    assert!(!fixtures.is_empty(), "No blueprints found");
+    
+    for fixture in fixtures {
+        let output = Command::new("cargo")
+            .args(["run", "--", "plan", "-f"])
+            .arg(&fixture)
+            .output()
+            .expect("Failed to execute command");
+        
+        assert_eq!(output.status.code(), Some(0),
+            "{} does not plan: {}", fixture.display(), String::from_utf8_lossy(&output.stderr));
+    }
+}
@@ -26,7 +26,10 @@ candidates:
 
         let result = Selector::new(corrupted_rules, 42, 8);
         assert!(result.is_err());
-        assert!(result.unwrap_err().contains("Failed to parse rules"));
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("Failed to parse rules"));
     }
 
     /// Test behavior with incomplete blueprint
@@ -130,7 +133,7 @@ traffic_profile:
 
         let result = selector.select(&blueprint);
         assert!(result.is_err());
-        let err_msg = result.unwrap_err();
+        let err_msg = result.unwrap_err().to_string();
         assert!(
             err_msg.contains("cost constraint")
                 || err_msg.contains("No suitable")
@@ -2,16 +2,18 @@ use proptest::prelude::*;
 use rand::Rng;
 use runeforge::schema::*;
 use runeforge::util::*;
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 
 // Property-based test strategies
 prop_compose! {
     fn arb_traffic_profile()(
         rps_peak in 0.0..10000.0,
         global in any::<bool>(),
-        latency_sensitive in any::<bool>()
+        latency_sensitive in any::<bool>(),
+        avg_payload_kb in prop::option::of(0.0..512.0),
+        read_ratio in prop::option::of(0.0..=1.0f64)
     ) -> TrafficProfile {
-        TrafficProfile { rps_peak, global, latency_sensitive }
+        TrafficProfile { rps_peak, global, latency_sensitive, avg_payload_kb, read_ratio }
     }
 }
 
@@ -48,18 +50,39 @@ prop_compose! {
             persistence,
             region_allow,
             compliance,
+            quality_min: None,
+            security_min: None,
+            slo_min: None,
         }
     }
 }
 
 prop_compose! {
     fn arb_preferences()(
-        frontend in prop::option::of(prop::collection::vec("[A-Za-z]+", 0..3)),
-        backend in prop::option::of(prop::collection::vec("[A-Za-z]+", 0..3)),
-        database in prop::option::of(prop::collection::vec("[A-Za-z]+", 0..3)),
-        ai in prop::option::of(prop::collection::vec("[A-Za-z]+", 0..3))
+        ranked in prop::collection::btree_map(
+            prop_oneof![
+                Just("frontend".to_string()),
+                Just("backend".to_string()),
+                Just("database".to_string()),
+                Just("cache".to_string()),
+                Just("ai".to_string()),
+                Just("api".to_string()),
+            ],
+            prop::collection::vec("[A-Za-z]+", 0..3),
+            0..4
+        ),
+        avoid in prop::collection::btree_map(
+            prop_oneof![
+                Just("database".to_string()),
+                Just("queue".to_string()),
+                Just("edge".to_string()),
+            ],
+            prop::collection::vec("[A-Za-z]+", 0..3),
+            0..2
+        ),
+        strength in prop::option::of(0.0..=1.0f64)
     ) -> Preferences {
-        Preferences { frontend, backend, database, ai }
+        Preferences { ranked, avoid, strength }
     }
 }
 
@@ -83,6 +106,8 @@ prop_compose! {
             traffic_profile,
             prefs,
             single_language_mode,
+            services: None,
+            categories: Default::default(),
         }
     }
 }
@@ -158,6 +183,8 @@ proptest! {
                 reasons: vec!["reason".to_string()],
                 alternatives: vec![],
                 score: 0.5, // Valid score
+                breakdown: None,
+                rejected: Vec::new(),
             })
             .collect();
 
@@ -166,14 +193,17 @@ proptest! {
             stack: Stack {
                 language: "Rust".to_string(),
                 services: None,
-                frontend: "SvelteKit".to_string(),
-                backend: "Actix".to_string(),
-                database: "PostgreSQL".to_string(),
-                cache: "Redis".to_string(),
-                queue: "NATS".to_string(),
-                ai: vec!["AI1".to_string()],
-                infra: "Terraform".to_string(),
-                ci_cd: "GitHub".to_string(),
+                data_roles: None,
+                components: BTreeMap::from([
+                    ("frontend".to_string(), "SvelteKit".into()),
+                    ("backend".to_string(), "Actix".into()),
+                    ("database".to_string(), "PostgreSQL".into()),
+                    ("cache".to_string(), "Redis".into()),
+                    ("queue".to_string(), "NATS".into()),
+                    ("ai".to_string(), vec!["AI1"].into()),
+                    ("infra".to_string(), "Terraform".into()),
+                    ("ci_cd".to_string(), "GitHub".into()),
+                ]),
             },
             estimated: Estimated {
                 monthly_cost_usd: monthly_cost,
@@ -216,6 +246,8 @@ proptest! {
             reasons: vec!["test".to_string()],
             alternatives: vec![],
             score: (quality + slo + cost + security + ops) / 5.0,
+            breakdown: None,
+            rejected: Vec::new(),
         };
 
         // Average of values in [0,1] should also be in [0,1]